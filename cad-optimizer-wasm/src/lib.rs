@@ -1,7 +1,9 @@
 use wasm_bindgen::prelude::*;
 use js_sys::{Float32Array, Uint32Array}; // Rimossi Array e Object non utilizzati
 use serde::{Serialize, Deserialize};
-use glam::{Vec3, Mat4};
+use glam::{Vec3, Vec4, Mat4};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 
 // Strutture dati per la comunicazione con JS
 #[derive(Serialize, Deserialize, Clone)] // Aggiunto Clone per risolvere l'errore
@@ -23,6 +25,100 @@ pub struct LodLevel {
     detail_ratio: f32,
 }
 
+// Sfera di delimitazione usata per i bound per-cluster
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BoundingSphere {
+    center: [f32; 3],
+    radius: f32,
+}
+
+// Un meshlet: piccolo cluster di triangoli con il proprio buffer di vertici locale
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Meshlet {
+    vertices: Vec<f32>,
+    indices: Vec<u32>,
+    bounds: BoundingSphere,
+    lod_level: u32,
+    error: f32,
+    parent_error: f32,
+}
+
+// Metadati di un singolo livello di LOD del DAG di meshlet
+#[derive(Serialize, Deserialize)]
+pub struct MeshletLevel {
+    lod: u32,
+    meshlet_count: u32,
+    error: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MeshletLod {
+    meshlets: Vec<Meshlet>,
+    levels: Vec<MeshletLevel>,
+}
+
+// Risultato della tassellazione di una patch di Bézier
+#[derive(Serialize, Deserialize)]
+pub struct TessellatedPatch {
+    vertices: Vec<f32>,
+    indices: Vec<u32>,
+    normals: Vec<f32>,
+}
+
+// Opzioni per l'unwrapping dell'atlante UV
+#[derive(Serialize, Deserialize)]
+pub struct UvAtlasOptions {
+    #[serde(default = "default_atlas_resolution")]
+    resolution: u32,
+    #[serde(default = "default_angle_threshold")]
+    angle_threshold: f32,
+    #[serde(default = "default_allow_rotation")]
+    allow_rotation: bool,
+    #[serde(default = "default_atlas_padding")]
+    padding: u32,
+}
+
+fn default_atlas_resolution() -> u32 { 1024 }
+fn default_angle_threshold() -> f32 { 1.047 } // ~60° in radianti
+fn default_allow_rotation() -> bool { true }
+fn default_atlas_padding() -> u32 { 2 }
+
+// Limiti di sicurezza: `pack_charts` alloca una griglia `resolution * resolution`,
+// quindi un valore JS non validato potrebbe far abortire l'istanza WASM
+const MAX_ATLAS_RESOLUTION: u32 = 8192;
+const MAX_ATLAS_PADDING: u32 = 256;
+
+// Valida `resolution`/`padding` prima che raggiungano `pack_charts`
+fn validate_atlas_limits(resolution: u32, padding: u32) -> Result<(), String> {
+    if resolution == 0 || resolution > MAX_ATLAS_RESOLUTION {
+        return Err(format!("resolution must be between 1 and {}", MAX_ATLAS_RESOLUTION));
+    }
+    if padding > MAX_ATLAS_PADDING {
+        return Err(format!("padding must be at most {}", MAX_ATLAS_PADDING));
+    }
+    Ok(())
+}
+
+impl Default for UvAtlasOptions {
+    fn default() -> Self {
+        UvAtlasOptions {
+            resolution: default_atlas_resolution(),
+            angle_threshold: default_angle_threshold(),
+            allow_rotation: default_allow_rotation(),
+            padding: default_atlas_padding(),
+        }
+    }
+}
+
+// Risultato dell'atlante UV
+#[derive(Serialize, Deserialize)]
+pub struct UvAtlasResult {
+    uvs: Vec<f32>,
+    width: u32,
+    height: u32,
+    vertex_remap: Vec<u32>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PerformanceSettings {
     target_fps: u32,
@@ -198,6 +294,122 @@ impl CADOptimizer {
         }
     }
     
+    // Costruisce un DAG di LOD a cluster (meshlet) a partire da una mesh densa
+    #[wasm_bindgen]
+    pub fn build_meshlet_lod(&self, vertices_js: Float32Array, indices_js: Uint32Array) -> Result<JsValue, JsValue> {
+        let vertices = convert_float32_array_to_vec(vertices_js);
+        let indices = convert_uint32_array_to_vec(indices_js);
+
+        let lod = build_meshlet_dag(&vertices, &indices);
+
+        match serde_wasm_bindgen::to_value(&lod) {
+            Ok(val) => Ok(val),
+            Err(e) => Err(JsValue::from_str(&format!("Failed to serialize meshlet LOD: {}", e))),
+        }
+    }
+
+    // Tassella una curva di Bézier con suddivisione adattiva di de Casteljau.
+    #[wasm_bindgen]
+    pub fn tessellate_bezier(&self, control_points_js: Float32Array, degree: u32, tolerance: f32) -> Result<Float32Array, JsValue> {
+        let control = convert_float32_array_to_vec(control_points_js);
+        let points = tessellate_bezier_curve(&control, degree as usize, tolerance);
+
+        let out = Float32Array::new_with_length(points.len() as u32);
+        out.copy_from(&points);
+        Ok(out)
+    }
+
+    // Tassella una patch di Bézier bicubica, generando normali smussate.
+    #[wasm_bindgen]
+    pub fn tessellate_bezier_patch(&self, control_points_js: Float32Array, tolerance: f32) -> Result<JsValue, JsValue> {
+        let control = convert_float32_array_to_vec(control_points_js);
+        let (vertices, indices) = tessellate_bezier_patch_impl(&control, tolerance);
+        let normals = self.compute_smooth_normals(&vertices, &indices);
+
+        let patch = TessellatedPatch { vertices, indices, normals };
+        match serde_wasm_bindgen::to_value(&patch) {
+            Ok(val) => Ok(val),
+            Err(e) => Err(JsValue::from_str(&format!("Failed to serialize tessellated patch: {}", e))),
+        }
+    }
+
+    // Post-processa una mesh: saldatura dei vertici, riordino degli indici per
+    // la cache dei vertici e riordino del buffer vertici. `epsilon` = 0 usa il
+    // valore predefinito di saldatura.
+    #[wasm_bindgen]
+    pub fn optimize_mesh(&self, vertices_js: Float32Array, indices_js: Uint32Array, epsilon: f32) -> Result<JsValue, JsValue> {
+        let vertices = convert_float32_array_to_vec(vertices_js);
+        let indices = convert_uint32_array_to_vec(indices_js);
+        let optimized = optimize_mesh_impl(&vertices, &indices, epsilon);
+
+        match serde_wasm_bindgen::to_value(&optimized) {
+            Ok(val) => Ok(val),
+            Err(e) => Err(JsValue::from_str(&format!("Failed to serialize optimized mesh: {}", e))),
+        }
+    }
+
+    // Estrae un'isosuperficie da un campo scalare 3D con il marching cubes.
+    #[wasm_bindgen]
+    pub fn marching_cubes(&self, field_js: Float32Array, dim_x: u32, dim_y: u32, dim_z: u32, iso: f32) -> Result<JsValue, JsValue> {
+        let field = convert_float32_array_to_vec(field_js);
+        let mesh = marching_cubes_impl(&field, [dim_x, dim_y, dim_z], iso);
+
+        match serde_wasm_bindgen::to_value(&mesh) {
+            Ok(val) => Ok(val),
+            Err(e) => Err(JsValue::from_str(&format!("Failed to serialize isosurface: {}", e))),
+        }
+    }
+
+    // Esporta una mesh in STL binario pronto per la stampa 3D.
+    #[wasm_bindgen]
+    pub fn export_stl(&self, vertices_js: Float32Array, indices_js: Uint32Array) -> Result<js_sys::Uint8Array, JsValue> {
+        let vertices = convert_float32_array_to_vec(vertices_js);
+        let indices = convert_uint32_array_to_vec(indices_js);
+        let bytes = build_binary_stl(&vertices, &indices);
+
+        let out = js_sys::Uint8Array::new_with_length(bytes.len() as u32);
+        out.copy_from(&bytes);
+        Ok(out)
+    }
+
+    // Calcola l'involucro convesso (QuickHull 3D) di una nuvola di punti.
+    // `max_vertices` = 0 indica nessun limite.
+    #[wasm_bindgen]
+    pub fn compute_convex_hull(&self, vertices_js: Float32Array, max_vertices: u32) -> Result<JsValue, JsValue> {
+        let vertices = convert_float32_array_to_vec(vertices_js);
+        let hull = quickhull(&vertices, max_vertices);
+
+        match serde_wasm_bindgen::to_value(&hull) {
+            Ok(val) => Ok(val),
+            Err(e) => Err(JsValue::from_str(&format!("Failed to serialize convex hull: {}", e))),
+        }
+    }
+
+    // Genera un atlante UV non sovrapposto per una mesh arbitraria
+    #[wasm_bindgen]
+    pub fn generate_uv_atlas(&self, vertices_js: Float32Array, indices_js: Uint32Array, options_js: JsValue) -> Result<JsValue, JsValue> {
+        let vertices = convert_float32_array_to_vec(vertices_js);
+        let indices = convert_uint32_array_to_vec(indices_js);
+
+        // Opzioni facoltative: usa i valori predefiniti se assenti
+        let options: UvAtlasOptions = if options_js.is_undefined() || options_js.is_null() {
+            UvAtlasOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options_js)
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse atlas options: {}", e)))?
+        };
+
+        // Valida i limiti prima del packing: `pack_charts` alloca una griglia `resolution^2`
+        validate_atlas_limits(options.resolution, options.padding).map_err(|e| JsValue::from_str(&e))?;
+
+        let result = generate_uv_atlas_impl(&vertices, &indices, &options);
+
+        match serde_wasm_bindgen::to_value(&result) {
+            Ok(val) => Ok(val),
+            Err(e) => Err(JsValue::from_str(&format!("Failed to serialize UV atlas: {}", e))),
+        }
+    }
+
     // Calcola le normali della mesh
     #[wasm_bindgen]
     pub fn calculate_normals(&self, vertices_js: Float32Array, indices_js: Uint32Array) -> Result<Float32Array, JsValue> {
@@ -228,80 +440,67 @@ impl CADOptimizer {
         
         self.check_box_in_frustum(min, max, view_proj_matrix)
     }
+
+    // Culling in batch: restituisce gli indici degli oggetti visibili in una
+    // singola chiamata WASM. `bboxes` contiene 6 float per oggetto
+    // (min xyz, max xyz). Rispetta `settings.frustum_culling`.
+    #[wasm_bindgen]
+    pub fn cull_objects(&self, bboxes: Float32Array, view_projection: Float32Array) -> Uint32Array {
+        let data = convert_float32_array_to_vec(bboxes);
+        let count = data.len() / 6;
+
+        // Con il culling disattivato sono visibili tutti gli oggetti
+        if !self.settings.frustum_culling {
+            let out = Uint32Array::new_with_length(count as u32);
+            for i in 0..count {
+                out.set_index(i as u32, i as u32);
+            }
+            return out;
+        }
+
+        let vp = convert_float32_array_to_mat4(view_projection);
+        let planes = extract_frustum_planes(vp);
+
+        let mut visible: Vec<u32> = Vec::new();
+        for i in 0..count {
+            let min = Vec3::new(data[i * 6], data[i * 6 + 1], data[i * 6 + 2]);
+            let max = Vec3::new(data[i * 6 + 3], data[i * 6 + 4], data[i * 6 + 5]);
+            if aabb_in_frustum(&planes, min, max) {
+                visible.push(i as u32);
+            }
+        }
+
+        let out = Uint32Array::new_with_length(visible.len() as u32);
+        out.copy_from(&visible);
+        out
+    }
 }
 
 // Metodi privati dell'implementazione
 impl CADOptimizer {
-    // Algoritmo di decimazione della mesh
+    // Algoritmo di decimazione della mesh basato su quadric error metrics (QEM)
     fn perform_decimation(&self, vertices: &[f32], indices: &[u32], ratio: f32) -> MeshData {
-        // Implementazione di un algoritmo di decimazione semplice
-        // In una versione reale, useresti un algoritmo più sofisticato
-        let _vertex_count = vertices.len() / 3; // Aggiunto underscore per evitare warning di variabile non usata
+        // Interpreta `ratio` come frazione di triangoli da mantenere
         let triangle_count = indices.len() / 3;
-        
-        // Calcola quanti triangoli tenere
-        let target_triangles = (triangle_count as f32 * ratio).max(1.0) as usize;
-        
-        // Semplifica rimuovendo triangoli uniformemente
-        let mut new_indices = Vec::with_capacity(target_triangles * 3);
-        let step = (triangle_count as f32 / target_triangles as f32).max(1.0);
-        
-        for i in (0..triangle_count).step_by(step as usize) {
-            if new_indices.len() < target_triangles * 3 {
-                new_indices.push(indices[i * 3]);
-                new_indices.push(indices[i * 3 + 1]);
-                new_indices.push(indices[i * 3 + 2]);
-            }
-        }
-        
-        // Tieni tutti i vertici originali per semplicità
-        // In una vera implementazione, elimineresti anche i vertici non utilizzati
-        MeshData {
-            vertices: vertices.to_vec(),
-            indices: new_indices,
-        }
+        let target_triangles = ((triangle_count as f32 * ratio).round() as usize).max(1);
+
+        // Delega al collasso di archi QEM, che rimuove sia triangoli che vertici
+        qem_simplify(vertices, indices, target_triangles)
     }
     
-    // Operazione booleana - Unione
+    // Operazione booleana - Unione (CSG basata su BSP)
     fn perform_boolean_union(&self, mesh_a: &MeshData, mesh_b: &MeshData) -> MeshData {
-        // In una vera implementazione, useresti una libreria CSG
-        // Questa è solo una dimostrazione semplificata
-        
-        // Combina semplicemente i vertici e gli indici
-        let mut result_vertices = mesh_a.vertices.clone();
-        let mut result_indices = mesh_a.indices.clone();
-        
-        let vertex_offset = result_vertices.len() / 3;
-        
-        // Aggiungi i vertici di mesh_b
-        result_vertices.extend_from_slice(&mesh_b.vertices);
-        
-        // Aggiungi gli indici di mesh_b, adattando per l'offset
-        for &idx in &mesh_b.indices {
-            result_indices.push(idx + vertex_offset as u32);
-        }
-        
-        MeshData {
-            vertices: result_vertices,
-            indices: result_indices,
-        }
+        csg_operation(mesh_a, mesh_b, CsgOp::Union)
     }
-    
-    // Operazione booleana - Sottrazione
-    fn perform_boolean_subtract(&self, mesh_a: &MeshData, _mesh_b: &MeshData) -> MeshData {
-        // Implementazione semplificata
-        // Ora clone() funziona perché abbiamo aggiunto #[derive(Clone)] a MeshData
-        mesh_a.clone()
+
+    // Operazione booleana - Sottrazione (CSG basata su BSP)
+    fn perform_boolean_subtract(&self, mesh_a: &MeshData, mesh_b: &MeshData) -> MeshData {
+        csg_operation(mesh_a, mesh_b, CsgOp::Subtract)
     }
-    
-    // Operazione booleana - Intersezione
+
+    // Operazione booleana - Intersezione (CSG basata su BSP)
     fn perform_boolean_intersect(&self, mesh_a: &MeshData, mesh_b: &MeshData) -> MeshData {
-        // Implementazione semplificata
-        // In una vera implementazione, calcoleresti l'intersezione reale
-        MeshData {
-            vertices: mesh_a.vertices[0..mesh_a.vertices.len().min(mesh_b.vertices.len())].to_vec(),
-            indices: mesh_a.indices[0..mesh_a.indices.len().min(mesh_b.indices.len())].to_vec(),
-        }
+        csg_operation(mesh_a, mesh_b, CsgOp::Intersect)
     }
     
     // Calcola le normali smussate per una mesh
@@ -365,33 +564,10 @@ impl CADOptimizer {
         normals
     }
     
-    // Verifica se un box è visibile nel frustum
+    // Verifica se un box è visibile nel frustum tramite i piani estratti
     fn check_box_in_frustum(&self, min: Vec3, max: Vec3, view_proj: Mat4) -> bool {
-        // Ottieni gli 8 angoli del box
-        let corners = [
-            Vec3::new(min.x, min.y, min.z),
-            Vec3::new(max.x, min.y, min.z),
-            Vec3::new(min.x, max.y, min.z),
-            Vec3::new(max.x, max.y, min.z),
-            Vec3::new(min.x, min.y, max.z),
-            Vec3::new(max.x, min.y, max.z),
-            Vec3::new(min.x, max.y, max.z),
-            Vec3::new(max.x, max.y, max.z),
-        ];
-        
-        // Verifica se almeno un angolo è dentro il frustum
-        for corner in &corners {
-            let clip_pos = view_proj.transform_point3(*corner);
-            
-            // Se tutti i componenti sono tra -w e w, il punto è visibile
-            if clip_pos.x >= -clip_pos.z && clip_pos.x <= clip_pos.z &&
-               clip_pos.y >= -clip_pos.z && clip_pos.y <= clip_pos.z &&
-               clip_pos.z >= -1.0 && clip_pos.z <= 1.0 {
-                return true;
-            }
-        }
-        
-        false
+        let planes = extract_frustum_planes(view_proj);
+        aabb_in_frustum(&planes, min, max)
     }
 }
 
@@ -415,10 +591,2814 @@ fn convert_float32_array_to_mat4(array: Float32Array) -> Mat4 {
     Mat4::from_cols_array(&matrix_data)
 }
 
-// Macro per il logging console
-#[macro_export]
-macro_rules! console_log {
-    ($($t:tt)*) => {
-        web_sys::console::log_1(&format!($($t)*).into());
+// ---------------------------------------------------------------------------
+// Semplificazione QEM (quadric error metrics) tramite collasso di archi
+// ---------------------------------------------------------------------------
+
+// Quadrica simmetrica 4x4 memorizzata come coefficienti del triangolo superiore:
+// (a2, ab, ac, ad, b2, bc, bd, c2, cd, d2), con il piano n = (a,b,c), d.
+type Quadric = [f64; 10];
+
+// Costruisce la quadrica K = n·nᵀ per un piano (a,b,c,d)
+fn quadric_from_plane(a: f64, b: f64, c: f64, d: f64) -> Quadric {
+    [
+        a * a, a * b, a * c, a * d,
+        b * b, b * c, b * d,
+        c * c, c * d,
+        d * d,
+    ]
+}
+
+fn quadric_add(q: &mut Quadric, o: &Quadric) {
+    for i in 0..10 {
+        q[i] += o[i];
+    }
+}
+
+// Errore vᵀ Q v con v = (x,y,z,1)
+fn quadric_error(q: &Quadric, v: Vec3) -> f64 {
+    let (x, y, z) = (v.x as f64, v.y as f64, v.z as f64);
+    q[0] * x * x + 2.0 * q[1] * x * y + 2.0 * q[2] * x * z + 2.0 * q[3] * x
+        + q[4] * y * y + 2.0 * q[5] * y * z + 2.0 * q[6] * y
+        + q[7] * z * z + 2.0 * q[8] * z
+        + q[9]
+}
+
+// Posizione ottimale che minimizza la quadrica risolvendo il sistema 3x3.
+// Restituisce None se la matrice è singolare (si ripiega sul punto medio).
+fn quadric_optimal(q: &Quadric) -> Option<Vec3> {
+    // A = [[a2, ab, ac], [ab, b2, bc], [ac, bc, c2]], rhs = -(ad, bd, cd)
+    let (a2, ab, ac) = (q[0], q[1], q[2]);
+    let (b2, bc) = (q[4], q[5]);
+    let c2 = q[7];
+    let det = a2 * (b2 * c2 - bc * bc) - ab * (ab * c2 - bc * ac) + ac * (ab * bc - b2 * ac);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let (rx, ry, rz) = (-q[3], -q[6], -q[8]);
+    let inv_det = 1.0 / det;
+    // Regola di Cramer
+    let x = (rx * (b2 * c2 - bc * bc) - ab * (ry * c2 - bc * rz) + ac * (ry * bc - b2 * rz)) * inv_det;
+    let y = (a2 * (ry * c2 - bc * rz) - rx * (ab * c2 - bc * ac) + ac * (ab * rz - ry * ac)) * inv_det;
+    let z = (a2 * (b2 * rz - ry * bc) - ab * (ab * rz - ry * ac) + rx * (ab * bc - b2 * ac)) * inv_det;
+    Some(Vec3::new(x as f32, y as f32, z as f32))
+}
+
+// Candidato di collasso nello heap, ordinato per costo crescente (min-heap)
+struct EdgeCollapse {
+    cost: f64,
+    v1: usize,
+    v2: usize,
+    ver1: u64,
+    ver2: u64,
+    target: Vec3,
+}
+
+impl PartialEq for EdgeCollapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+impl Eq for EdgeCollapse {}
+impl Ord for EdgeCollapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Invertito: BinaryHeap è un max-heap, vogliamo il costo minore in cima
+        other.cost.total_cmp(&self.cost)
+    }
+}
+impl PartialOrd for EdgeCollapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn vertex_at(vertices: &[f32], i: usize) -> Vec3 {
+    Vec3::new(vertices[i * 3], vertices[i * 3 + 1], vertices[i * 3 + 2])
+}
+
+// Collasso di archi QEM fino al numero di triangoli richiesto.
+// Restituisce una MeshData compattata senza vertici inutilizzati; se la mesh
+// ha già al più `target_triangles` triangoli la restituisce invariata.
+fn qem_simplify(vertices: &[f32], indices: &[u32], target_triangles: usize) -> MeshData {
+    let vertex_count = vertices.len() / 3;
+    let triangle_count = indices.len() / 3;
+    if vertex_count == 0 || triangle_count <= target_triangles {
+        return MeshData { vertices: vertices.to_vec(), indices: indices.to_vec() };
+    }
+
+    // Posizioni correnti dei vertici (mutate dai collassi)
+    let mut pos: Vec<Vec3> = (0..vertex_count).map(|i| vertex_at(vertices, i)).collect();
+    let mut alive = vec![true; vertex_count];
+    let mut version = vec![0u64; vertex_count];
+
+    // Triangoli come terne di indici, con flag di validità
+    let mut tris: Vec<[usize; 3]> = (0..triangle_count)
+        .map(|t| [indices[t * 3] as usize, indices[t * 3 + 1] as usize, indices[t * 3 + 2] as usize])
+        .collect();
+    let mut tri_alive = vec![true; triangle_count];
+
+    // Adiacenza vertice -> triangoli incidenti
+    let mut vtri: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    for (t, tri) in tris.iter().enumerate() {
+        for &v in tri {
+            vtri[v].push(t);
+        }
+    }
+
+    // Molteplicità degli archi e vertici di bordo (calcolati una sola volta)
+    let mut edge_count: std::collections::HashMap<(usize, usize), u32> = std::collections::HashMap::new();
+    for tri in &tris {
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            let key = if a < b { (a, b) } else { (b, a) };
+            *edge_count.entry(key).or_insert(0) += 1;
+        }
+    }
+    let mut boundary = vec![false; vertex_count];
+    for (&(a, b), &c) in &edge_count {
+        if c == 1 {
+            boundary[a] = true;
+            boundary[b] = true;
+        }
+    }
+
+    // Quadriche iniziali per vertice
+    let mut quad = vec![[0.0f64; 10]; vertex_count];
+    for tri in &tris {
+        let (v0, v1, v2) = (pos[tri[0]], pos[tri[1]], pos[tri[2]]);
+        let n = (v1 - v0).cross(v2 - v0);
+        let len = n.length();
+        if len < 1e-12 {
+            continue;
+        }
+        let n = n / len;
+        let (a, b, c) = (n.x as f64, n.y as f64, n.z as f64);
+        let d = -(n.dot(v0) as f64);
+        let k = quadric_from_plane(a, b, c, d);
+        for &v in tri {
+            quad[v] = {
+                let mut q = quad[v];
+                quadric_add(&mut q, &k);
+                q
+            };
+        }
+    }
+
+    // Normale (non normalizzata) di un triangolo con posizioni correnti
+    let tri_normal = |pos: &[Vec3], tri: &[usize; 3]| -> Vec3 {
+        (pos[tri[1]] - pos[tri[0]]).cross(pos[tri[2]] - pos[tri[0]])
+    };
+
+    // Costo e posizione ottimale di un collasso (v1,v2)
+    let eval = |quad: &[Quadric], pos: &[Vec3], v1: usize, v2: usize| -> (f64, Vec3) {
+        let mut q = quad[v1];
+        quadric_add(&mut q, &quad[v2]);
+        let target = quadric_optimal(&q).unwrap_or_else(|| (pos[v1] + pos[v2]) * 0.5);
+        (quadric_error(&q, target), target)
+    };
+
+    // Heap iniziale degli archi unici
+    let mut heap: BinaryHeap<EdgeCollapse> = BinaryHeap::new();
+    for &(a, b) in edge_count.keys() {
+        let (cost, target) = eval(&quad, &pos, a, b);
+        heap.push(EdgeCollapse { cost, v1: a, v2: b, ver1: version[a], ver2: version[b], target });
+    }
+
+    let mut live_triangles = triangle_count;
+
+    while live_triangles > target_triangles {
+        let cand = match heap.pop() {
+            Some(c) => c,
+            None => break,
+        };
+        let (v1, v2) = (cand.v1, cand.v2);
+        // Scarta candidati obsoleti o non più validi
+        if !alive[v1] || !alive[v2] || cand.ver1 != version[v1] || cand.ver2 != version[v2] {
+            continue;
+        }
+
+        // Rispetta bordi e archi non-manifold: consenti solo se entrambi i
+        // vertici sono di bordo oppure l'arco è regolare (condiviso da 2 facce)
+        let key = if v1 < v2 { (v1, v2) } else { (v2, v1) };
+        let mult = *edge_count.get(&key).unwrap_or(&0);
+        if mult != 2 && !(boundary[v1] && boundary[v2]) {
+            continue;
+        }
+
+        let target = cand.target;
+
+        // Verifica di flip delle normali: v2 viene fuso in v1 spostato su target
+        let mut flips = false;
+        for &t in vtri[v1].iter().chain(vtri[v2].iter()) {
+            if !tri_alive[t] {
+                continue;
+            }
+            let tri = tris[t];
+            // I triangoli che contengono entrambi i vertici degenerano: saltali
+            if tri.contains(&v1) && tri.contains(&v2) {
+                continue;
+            }
+            let before = tri_normal(&pos, &tri);
+            let mut moved = tri;
+            for s in moved.iter_mut() {
+                if *s == v2 {
+                    *s = v1;
+                }
+            }
+            let after = {
+                let p0 = if moved[0] == v1 { target } else { pos[moved[0]] };
+                let p1 = if moved[1] == v1 { target } else { pos[moved[1]] };
+                let p2 = if moved[2] == v1 { target } else { pos[moved[2]] };
+                (p1 - p0).cross(p2 - p0)
+            };
+            if before.dot(after) < 0.0 {
+                flips = true;
+                break;
+            }
+        }
+        if flips {
+            continue;
+        }
+
+        // Esegui il collasso: v1 sopravvive su target, v2 muore
+        pos[v1] = target;
+        quad[v1] = {
+            let mut q = quad[v1];
+            quadric_add(&mut q, &quad[v2]);
+            q
+        };
+        boundary[v1] = boundary[v1] || boundary[v2];
+        alive[v2] = false;
+
+        // Aggiorna i triangoli incidenti a v2 e rimuovi quelli degeneri
+        let affected: Vec<usize> = vtri[v2].clone();
+        for &t in &affected {
+            if !tri_alive[t] {
+                continue;
+            }
+            for s in tris[t].iter_mut() {
+                if *s == v2 {
+                    *s = v1;
+                }
+            }
+            let tri = tris[t];
+            if tri[0] == tri[1] || tri[1] == tri[2] || tri[0] == tri[2] {
+                tri_alive[t] = false;
+                live_triangles -= 1;
+            } else {
+                vtri[v1].push(t);
+            }
+        }
+
+        // Incrementa le versioni dei vertici adiacenti per invalidare lo heap
+        version[v1] += 1;
+        version[v2] += 1;
+
+        // Ricalcola gli archi che toccano v1
+        let mut neighbors: Vec<usize> = Vec::new();
+        for &t in &vtri[v1] {
+            if !tri_alive[t] {
+                continue;
+            }
+            for &s in &tris[t] {
+                if s != v1 && alive[s] && !neighbors.contains(&s) {
+                    neighbors.push(s);
+                }
+            }
+        }
+        for s in neighbors {
+            version[s] += 1;
+            let (cost, tgt) = eval(&quad, &pos, v1, s);
+            heap.push(EdgeCollapse { cost, v1, v2: s, ver1: version[v1], ver2: version[s], target: tgt });
+        }
+    }
+
+    // Compatta: raccogli i triangoli vivi e rimappa i vertici ancora usati
+    let mut remap = vec![u32::MAX; vertex_count];
+    let mut out_vertices: Vec<f32> = Vec::new();
+    let mut out_indices: Vec<u32> = Vec::new();
+    for (t, tri) in tris.iter().enumerate() {
+        if !tri_alive[t] {
+            continue;
+        }
+        for &v in tri {
+            if remap[v] == u32::MAX {
+                remap[v] = (out_vertices.len() / 3) as u32;
+                out_vertices.push(pos[v].x);
+                out_vertices.push(pos[v].y);
+                out_vertices.push(pos[v].z);
+            }
+            out_indices.push(remap[v]);
+        }
+    }
+
+    MeshData { vertices: out_vertices, indices: out_indices }
+}
+
+// ---------------------------------------------------------------------------
+// Generazione del DAG di LOD a cluster (meshlet)
+// ---------------------------------------------------------------------------
+
+const MESHLET_MAX_VERTICES: usize = 64;
+const MESHLET_MAX_TRIANGLES: usize = 124;
+const MESHLET_GROUP_SIZE: usize = 4;
+
+// Sfera di delimitazione (centroide + raggio massimo) di un insieme di punti
+fn bounding_sphere(points: &[Vec3]) -> BoundingSphere {
+    if points.is_empty() {
+        return BoundingSphere { center: [0.0, 0.0, 0.0], radius: 0.0 };
+    }
+    let mut center = Vec3::ZERO;
+    for p in points {
+        center += *p;
+    }
+    center /= points.len() as f32;
+    let mut radius = 0.0f32;
+    for p in points {
+        radius = radius.max((*p - center).length());
+    }
+    BoundingSphere { center: [center.x, center.y, center.z], radius }
+}
+
+// Partiziona i triangoli in meshlet facendo crescere in modo greedy un cluster
+// da un triangolo seme, preferendo i triangoli che condividono un arco e che
+// mantengono stretto il cono di orientamento del cluster.
+fn partition_meshlets(vertices: &[f32], indices: &[u32]) -> Vec<Vec<usize>> {
+    let tcount = indices.len() / 3;
+    if tcount == 0 {
+        return Vec::new();
+    }
+    let tris: Vec<[usize; 3]> = (0..tcount)
+        .map(|t| [indices[t * 3] as usize, indices[t * 3 + 1] as usize, indices[t * 3 + 2] as usize])
+        .collect();
+
+    // Mappa arco -> triangoli per derivare l'adiacenza fra facce
+    let mut edge_map: std::collections::HashMap<(usize, usize), Vec<usize>> = std::collections::HashMap::new();
+    for (t, tri) in tris.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            let key = if a < b { (a, b) } else { (b, a) };
+            edge_map.entry(key).or_default().push(t);
+        }
+    }
+
+    // Normale di faccia normalizzata
+    let normal = |tri: &[usize; 3]| -> Vec3 {
+        let v0 = vertex_at(vertices, tri[0]);
+        let v1 = vertex_at(vertices, tri[1]);
+        let v2 = vertex_at(vertices, tri[2]);
+        (v1 - v0).cross(v2 - v0).normalize_or_zero()
+    };
+
+    let mut used = vec![false; tcount];
+    let mut clusters: Vec<Vec<usize>> = Vec::new();
+
+    for seed in 0..tcount {
+        if used[seed] {
+            continue;
+        }
+        let mut cluster = vec![seed];
+        used[seed] = true;
+        let mut verts: std::collections::HashSet<usize> = tris[seed].iter().copied().collect();
+        let mut cone = normal(&tris[seed]);
+
+        loop {
+            if cluster.len() >= MESHLET_MAX_TRIANGLES {
+                break;
+            }
+            // Raccogli i vicini candidati (triangoli non usati che condividono un arco)
+            let mut best: Option<(usize, f32)> = None;
+            for &t in &cluster {
+                for k in 0..3 {
+                    let (a, b) = (tris[t][k], tris[t][(k + 1) % 3]);
+                    let key = if a < b { (a, b) } else { (b, a) };
+                    if let Some(adj) = edge_map.get(&key) {
+                        for &cand in adj {
+                            if used[cand] {
+                                continue;
+                            }
+                            // Verifica il limite di vertici unici
+                            let added = tris[cand].iter().filter(|v| !verts.contains(v)).count();
+                            if verts.len() + added > MESHLET_MAX_VERTICES {
+                                continue;
+                            }
+                            // Preferisci il candidato che mantiene stretto il cono
+                            let align = cone.dot(normal(&tris[cand]));
+                            match best {
+                                Some((_, score)) if score >= align => {}
+                                _ => best = Some((cand, align)),
+                            }
+                        }
+                    }
+                }
+            }
+
+            match best {
+                Some((cand, _)) => {
+                    used[cand] = true;
+                    for &v in &tris[cand] {
+                        verts.insert(v);
+                    }
+                    // Aggiorna il cono come media delle normali
+                    cone = (cone + normal(&tris[cand])).normalize_or_zero();
+                    cluster.push(cand);
+                }
+                None => break,
+            }
+        }
+
+        clusters.push(cluster);
+    }
+
+    clusters
+}
+
+// Estrae un meshlet autoconsistente (vertici locali + indici) da un cluster di
+// triangoli della geometria corrente.
+fn cluster_to_meshlet(
+    vertices: &[f32],
+    indices: &[u32],
+    cluster: &[usize],
+    lod_level: u32,
+    error: f32,
+    parent_error: f32,
+) -> Meshlet {
+    let mut remap: std::collections::HashMap<usize, u32> = std::collections::HashMap::new();
+    let mut local_verts: Vec<f32> = Vec::new();
+    let mut local_indices: Vec<u32> = Vec::new();
+    let mut points: Vec<Vec3> = Vec::new();
+
+    for &t in cluster {
+        for k in 0..3 {
+            let v = indices[t * 3 + k] as usize;
+            let idx = *remap.entry(v).or_insert_with(|| {
+                let p = vertex_at(vertices, v);
+                local_verts.push(p.x);
+                local_verts.push(p.y);
+                local_verts.push(p.z);
+                points.push(p);
+                (points.len() - 1) as u32
+            });
+            local_indices.push(idx);
+        }
+    }
+
+    Meshlet {
+        vertices: local_verts,
+        indices: local_indices,
+        bounds: bounding_sphere(&points),
+        lod_level,
+        error,
+        parent_error,
+    }
+}
+
+// Raggruppa i cluster topologicamente connessi (che condividono archi di
+// triangoli) in gruppi di dimensione limitata tramite BFS sull'adiacenza.
+fn group_meshlets(clusters: &[Vec<usize>], indices: &[u32], tcount: usize) -> Vec<Vec<usize>> {
+    // Triangolo -> cluster
+    let mut tri_cluster = vec![usize::MAX; tcount];
+    for (c, cluster) in clusters.iter().enumerate() {
+        for &t in cluster {
+            tri_cluster[t] = c;
+        }
+    }
+
+    // Adiacenza fra cluster tramite archi condivisi
+    let mut edge_map: std::collections::HashMap<(usize, usize), Vec<usize>> = std::collections::HashMap::new();
+    for t in 0..tcount {
+        for k in 0..3 {
+            let (a, b) = (indices[t * 3 + k] as usize, indices[t * 3 + (k + 1) % 3] as usize);
+            let key = if a < b { (a, b) } else { (b, a) };
+            edge_map.entry(key).or_default().push(t);
+        }
+    }
+    let mut adj: Vec<std::collections::HashSet<usize>> = vec![std::collections::HashSet::new(); clusters.len()];
+    for tlist in edge_map.values() {
+        for i in 0..tlist.len() {
+            for j in (i + 1)..tlist.len() {
+                let (ca, cb) = (tri_cluster[tlist[i]], tri_cluster[tlist[j]]);
+                if ca != cb {
+                    adj[ca].insert(cb);
+                    adj[cb].insert(ca);
+                }
+            }
+        }
+    }
+
+    let mut assigned = vec![false; clusters.len()];
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for start in 0..clusters.len() {
+        if assigned[start] {
+            continue;
+        }
+        // BFS limitata a MESHLET_GROUP_SIZE
+        let mut group = vec![start];
+        assigned[start] = true;
+        let mut frontier = vec![start];
+        while group.len() < MESHLET_GROUP_SIZE {
+            let mut next = None;
+            'outer: for &c in &frontier {
+                for &n in &adj[c] {
+                    if !assigned[n] {
+                        next = Some(n);
+                        break 'outer;
+                    }
+                }
+            }
+            match next {
+                Some(n) => {
+                    assigned[n] = true;
+                    group.push(n);
+                    frontier.push(n);
+                }
+                None => break,
+            }
+        }
+        groups.push(group);
+    }
+
+    groups
+}
+
+// Estrae la geometria combinata (vertici + indici rimappati) dei triangoli di
+// un gruppo di cluster.
+fn extract_group_geometry(vertices: &[f32], indices: &[u32], tris: &[usize]) -> (Vec<f32>, Vec<u32>) {
+    let mut remap: std::collections::HashMap<usize, u32> = std::collections::HashMap::new();
+    let mut out_verts: Vec<f32> = Vec::new();
+    let mut out_indices: Vec<u32> = Vec::new();
+    for &t in tris {
+        for k in 0..3 {
+            let v = indices[t * 3 + k] as usize;
+            let idx = *remap.entry(v).or_insert_with(|| {
+                out_verts.push(vertices[v * 3]);
+                out_verts.push(vertices[v * 3 + 1]);
+                out_verts.push(vertices[v * 3 + 2]);
+                (out_verts.len() / 3 - 1) as u32
+            });
+            out_indices.push(idx);
+        }
+    }
+    (out_verts, out_indices)
+}
+
+// Costruisce il DAG di meshlet completo: partiziona, poi raffina iterativamente
+// raggruppando, semplificando del ~50% con QEM e ri-partizionando.
+fn build_meshlet_dag(vertices: &[f32], indices: &[u32]) -> MeshletLod {
+    let mut meshlets: Vec<Meshlet> = Vec::new();
+    let mut levels: Vec<MeshletLevel> = Vec::new();
+
+    let mut cur_verts = vertices.to_vec();
+    let mut cur_indices = indices.to_vec();
+    let mut lod: u32 = 0;
+    let mut level_error: f32 = 0.0;
+
+    loop {
+        let clusters = partition_meshlets(&cur_verts, &cur_indices);
+        if clusters.is_empty() {
+            break;
+        }
+
+        // Errore con cui il gruppo padre (livello più grossolano) subentra:
+        // mezzo raggio medio dei cluster, mantenendo l'errore monotono crescente
+        let mut mean_radius = 0.0f32;
+        for cluster in &clusters {
+            let mut points: Vec<Vec3> = Vec::new();
+            for &t in cluster {
+                for k in 0..3 {
+                    points.push(vertex_at(&cur_verts, cur_indices[t * 3 + k] as usize));
+                }
+            }
+            mean_radius += bounding_sphere(&points).radius;
+        }
+        mean_radius /= clusters.len() as f32;
+        let delta = (mean_radius * 0.5).max(f32::EPSILON);
+        let parent_error = level_error + delta;
+
+        for cluster in &clusters {
+            meshlets.push(cluster_to_meshlet(&cur_verts, &cur_indices, cluster, lod, level_error, parent_error));
+        }
+        levels.push(MeshletLevel { lod, meshlet_count: clusters.len() as u32, error: level_error });
+
+        // Termina quando rimane un solo gruppo/cluster da fondere
+        if clusters.len() <= 1 {
+            break;
+        }
+
+        let groups = group_meshlets(&clusters, &cur_indices, cur_indices.len() / 3);
+
+        // Costruisci la geometria del livello successivo fondendo e semplificando
+        let mut next_verts: Vec<f32> = Vec::new();
+        let mut next_indices: Vec<u32> = Vec::new();
+        for group in &groups {
+            let mut group_tris: Vec<usize> = Vec::new();
+            for &c in group {
+                group_tris.extend_from_slice(&clusters[c]);
+            }
+            let (gv, gi) = extract_group_geometry(&cur_verts, &cur_indices, &group_tris);
+            let target = (gi.len() / 3 / 2).max(1);
+            let simplified = qem_simplify(&gv, &gi, target);
+            let offset = (next_verts.len() / 3) as u32;
+            next_verts.extend_from_slice(&simplified.vertices);
+            for &i in &simplified.indices {
+                next_indices.push(i + offset);
+            }
+        }
+
+        // Se la semplificazione non ha ridotto nulla, evita un ciclo infinito
+        if next_indices.len() >= cur_indices.len() {
+            break;
+        }
+
+        cur_verts = next_verts;
+        cur_indices = next_indices;
+        level_error += delta;
+        lod += 1;
+    }
+
+    MeshletLod { meshlets, levels }
+}
+
+// ---------------------------------------------------------------------------
+// CSG (constructive solid geometry) basata su alberi BSP
+// ---------------------------------------------------------------------------
+
+const CSG_EPSILON: f32 = 1e-5;
+
+#[derive(Clone, Copy)]
+enum CsgOp {
+    Union,
+    Subtract,
+    Intersect,
+}
+
+// Piano di taglio n·p = w
+#[derive(Clone)]
+struct Plane {
+    normal: Vec3,
+    w: f32,
+}
+
+impl Plane {
+    fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Option<Plane> {
+        let n = (b - a).cross(c - a);
+        if n.length() < CSG_EPSILON {
+            return None;
+        }
+        let normal = n.normalize();
+        Some(Plane { normal, w: normal.dot(a) })
+    }
+
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.w = -self.w;
+    }
+
+    // Suddivide un poligono rispetto a questo piano nelle quattro liste csg.js
+    fn split_polygon(
+        &self,
+        polygon: &Polygon,
+        coplanar_front: &mut Vec<Polygon>,
+        coplanar_back: &mut Vec<Polygon>,
+        front: &mut Vec<Polygon>,
+        back: &mut Vec<Polygon>,
+    ) {
+        const COPLANAR: i32 = 0;
+        const FRONT: i32 = 1;
+        const BACK: i32 = 2;
+        const SPANNING: i32 = 3;
+
+        let mut polygon_type = 0;
+        let mut types: Vec<i32> = Vec::with_capacity(polygon.vertices.len());
+        for v in &polygon.vertices {
+            let t = self.normal.dot(*v) - self.w;
+            let ty = if t < -CSG_EPSILON {
+                BACK
+            } else if t > CSG_EPSILON {
+                FRONT
+            } else {
+                COPLANAR
+            };
+            polygon_type |= ty;
+            types.push(ty);
+        }
+
+        match polygon_type {
+            COPLANAR => {
+                if self.normal.dot(polygon.plane.normal) > 0.0 {
+                    coplanar_front.push(polygon.clone());
+                } else {
+                    coplanar_back.push(polygon.clone());
+                }
+            }
+            FRONT => front.push(polygon.clone()),
+            BACK => back.push(polygon.clone()),
+            _ => {
+                // SPANNING: suddividi il poligono lungo gli archi che attraversano
+                let n = polygon.vertices.len();
+                let mut f: Vec<Vec3> = Vec::new();
+                let mut b: Vec<Vec3> = Vec::new();
+                for i in 0..n {
+                    let j = (i + 1) % n;
+                    let (ti, tj) = (types[i], types[j]);
+                    let (vi, vj) = (polygon.vertices[i], polygon.vertices[j]);
+                    if ti != BACK {
+                        f.push(vi);
+                    }
+                    if ti != FRONT {
+                        b.push(vi);
+                    }
+                    if (ti | tj) == SPANNING {
+                        let t = (self.w - self.normal.dot(vi)) / self.normal.dot(vj - vi);
+                        let v = vi + (vj - vi) * t;
+                        f.push(v);
+                        b.push(v);
+                    }
+                }
+                if f.len() >= 3 {
+                    if let Some(p) = Polygon::new(f) {
+                        front.push(p);
+                    }
+                }
+                if b.len() >= 3 {
+                    if let Some(p) = Polygon::new(b) {
+                        back.push(p);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Polygon {
+    vertices: Vec<Vec3>,
+    plane: Plane,
+}
+
+impl Polygon {
+    fn new(vertices: Vec<Vec3>) -> Option<Polygon> {
+        let plane = Plane::from_points(vertices[0], vertices[1], vertices[2])?;
+        Some(Polygon { vertices, plane })
+    }
+
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        self.plane.flip();
+    }
+}
+
+// Nodo dell'albero BSP
+struct BspNode {
+    plane: Option<Plane>,
+    polygons: Vec<Polygon>,
+    front: Option<Box<BspNode>>,
+    back: Option<Box<BspNode>>,
+}
+
+impl BspNode {
+    fn new() -> BspNode {
+        BspNode { plane: None, polygons: Vec::new(), front: None, back: None }
+    }
+
+    fn from_polygons(polygons: Vec<Polygon>) -> BspNode {
+        let mut node = BspNode::new();
+        node.build(polygons);
+        node
+    }
+
+    // Inverte il solido: capovolge ogni poligono, piano e scambia i figli
+    fn invert(&mut self) {
+        for p in &mut self.polygons {
+            p.flip();
+        }
+        if let Some(plane) = &mut self.plane {
+            plane.flip();
+        }
+        if let Some(f) = &mut self.front {
+            f.invert();
+        }
+        if let Some(b) = &mut self.back {
+            b.invert();
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    // Rimuove le parti dei poligoni che si trovano dentro questo albero
+    fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+        let plane = match &self.plane {
+            Some(p) => p.clone(),
+            None => return polygons,
+        };
+        let mut front: Vec<Polygon> = Vec::new();
+        let mut back: Vec<Polygon> = Vec::new();
+        let mut coplanar_front: Vec<Polygon> = Vec::new();
+        let mut coplanar_back: Vec<Polygon> = Vec::new();
+        for poly in &polygons {
+            plane.split_polygon(poly, &mut coplanar_front, &mut coplanar_back, &mut front, &mut back);
+        }
+        // I poligoni complanari seguono l'orientamento del piano del nodo
+        front.append(&mut coplanar_front);
+        back.append(&mut coplanar_back);
+        let mut front = match &self.front {
+            Some(f) => f.clip_polygons(front),
+            None => front,
+        };
+        let back = match &self.back {
+            Some(b) => b.clip_polygons(back),
+            None => Vec::new(),
+        };
+        front.extend(back);
+        front
+    }
+
+    // Ritaglia questo albero rispetto a `bsp`
+    fn clip_to(&mut self, bsp: &BspNode) {
+        self.polygons = bsp.clip_polygons(std::mem::take(&mut self.polygons));
+        if let Some(f) = &mut self.front {
+            f.clip_to(bsp);
+        }
+        if let Some(b) = &mut self.back {
+            b.clip_to(bsp);
+        }
+    }
+
+    fn all_polygons(&self) -> Vec<Polygon> {
+        let mut out = self.polygons.clone();
+        if let Some(f) = &self.front {
+            out.extend(f.all_polygons());
+        }
+        if let Some(b) = &self.back {
+            out.extend(b.all_polygons());
+        }
+        out
+    }
+
+    fn build(&mut self, polygons: Vec<Polygon>) {
+        if polygons.is_empty() {
+            return;
+        }
+        if self.plane.is_none() {
+            self.plane = Some(polygons[0].plane.clone());
+        }
+        let plane = self.plane.clone().unwrap();
+        let mut front: Vec<Polygon> = Vec::new();
+        let mut back: Vec<Polygon> = Vec::new();
+        let mut coplanar_front: Vec<Polygon> = Vec::new();
+        let mut coplanar_back: Vec<Polygon> = Vec::new();
+        for poly in &polygons {
+            plane.split_polygon(poly, &mut coplanar_front, &mut coplanar_back, &mut front, &mut back);
+        }
+        self.polygons.append(&mut coplanar_front);
+        self.polygons.append(&mut coplanar_back);
+        if !front.is_empty() {
+            self.front.get_or_insert_with(|| Box::new(BspNode::new())).build(front);
+        }
+        if !back.is_empty() {
+            self.back.get_or_insert_with(|| Box::new(BspNode::new())).build(back);
+        }
+    }
+}
+
+// Converte una MeshData triangolata in poligoni CSG
+fn mesh_to_polygons(mesh: &MeshData) -> Vec<Polygon> {
+    let mut polys = Vec::new();
+    for t in 0..(mesh.indices.len() / 3) {
+        let a = vertex_at(&mesh.vertices, mesh.indices[t * 3] as usize);
+        let b = vertex_at(&mesh.vertices, mesh.indices[t * 3 + 1] as usize);
+        let c = vertex_at(&mesh.vertices, mesh.indices[t * 3 + 2] as usize);
+        if let Some(p) = Polygon::new(vec![a, b, c]) {
+            polys.push(p);
+        }
+    }
+    polys
+}
+
+// Triangola una zuppa di poligoni (fan) producendo una MeshData
+fn polygons_to_mesh(polygons: &[Polygon]) -> MeshData {
+    let mut vertices: Vec<f32> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    for poly in polygons {
+        if poly.vertices.len() < 3 {
+            continue;
+        }
+        let base = (vertices.len() / 3) as u32;
+        for v in &poly.vertices {
+            vertices.push(v.x);
+            vertices.push(v.y);
+            vertices.push(v.z);
+        }
+        for i in 1..(poly.vertices.len() as u32 - 1) {
+            indices.push(base);
+            indices.push(base + i);
+            indices.push(base + i + 1);
+        }
+    }
+    MeshData { vertices, indices }
+}
+
+// Esegue l'operazione CSG richiesta tramite le operazioni di clip classiche
+fn csg_operation(mesh_a: &MeshData, mesh_b: &MeshData, op: CsgOp) -> MeshData {
+    let mut a = BspNode::from_polygons(mesh_to_polygons(mesh_a));
+    let mut b = BspNode::from_polygons(mesh_to_polygons(mesh_b));
+
+    match op {
+        CsgOp::Union => {
+            a.clip_to(&b);
+            b.clip_to(&a);
+            b.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.build(b.all_polygons());
+        }
+        CsgOp::Subtract => {
+            a.invert();
+            a.clip_to(&b);
+            b.clip_to(&a);
+            b.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.build(b.all_polygons());
+            a.invert();
+        }
+        CsgOp::Intersect => {
+            a.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.clip_to(&b);
+            b.clip_to(&a);
+            a.build(b.all_polygons());
+            a.invert();
+        }
+    }
+
+    let mesh = polygons_to_mesh(&a.all_polygons());
+    // polygons_to_mesh emette vertici non saldati per ogni poligono: salda per condividere
+    // gli indici sugli spigoli adiacenti e ottenere una mesh chiusa
+    let (vertices, indices) = weld_vertices(&mesh.vertices, &mesh.indices, CSG_EPSILON);
+    MeshData { vertices, indices }
+}
+
+// ---------------------------------------------------------------------------
+// Unwrapping dell'atlante UV: segmentazione in chart, LSCM e packing
+// ---------------------------------------------------------------------------
+
+// Numero complesso minimale usato dal solver LSCM
+#[derive(Clone, Copy)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+    fn zero() -> Complex {
+        Complex { re: 0.0, im: 0.0 }
+    }
+    fn conj(self) -> Complex {
+        Complex { re: self.re, im: -self.im }
+    }
+    fn add(self, o: Complex) -> Complex {
+        Complex { re: self.re + o.re, im: self.im + o.im }
+    }
+    fn sub(self, o: Complex) -> Complex {
+        Complex { re: self.re - o.re, im: self.im - o.im }
+    }
+    fn mul(self, o: Complex) -> Complex {
+        Complex {
+            re: self.re * o.re - self.im * o.im,
+            im: self.re * o.im + self.im * o.re,
+        }
+    }
+    fn norm_sq(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+    fn div(self, o: Complex) -> Complex {
+        let d = o.norm_sq();
+        let n = self.mul(o.conj());
+        Complex { re: n.re / d, im: n.im / d }
+    }
+}
+
+// Segmenta la mesh in chart facendo crescere regioni le cui normali restano
+// entro la soglia angolare rispetto alla normale media della regione.
+fn segment_charts(vertices: &[f32], indices: &[u32], angle_threshold: f32) -> (Vec<usize>, usize) {
+    let tcount = indices.len() / 3;
+    let tris: Vec<[usize; 3]> = (0..tcount)
+        .map(|t| [indices[t * 3] as usize, indices[t * 3 + 1] as usize, indices[t * 3 + 2] as usize])
+        .collect();
+
+    let normal = |tri: &[usize; 3]| -> Vec3 {
+        let v0 = vertex_at(vertices, tri[0]);
+        let v1 = vertex_at(vertices, tri[1]);
+        let v2 = vertex_at(vertices, tri[2]);
+        (v1 - v0).cross(v2 - v0).normalize_or_zero()
+    };
+
+    // Adiacenza di faccia tramite archi condivisi
+    let mut edge_map: std::collections::HashMap<(usize, usize), Vec<usize>> = std::collections::HashMap::new();
+    for (t, tri) in tris.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            let key = if a < b { (a, b) } else { (b, a) };
+            edge_map.entry(key).or_default().push(t);
+        }
+    }
+
+    let cos_thr = angle_threshold.cos();
+    let mut chart_of = vec![usize::MAX; tcount];
+    let mut num_charts = 0;
+    for seed in 0..tcount {
+        if chart_of[seed] != usize::MAX {
+            continue;
+        }
+        let chart = num_charts;
+        num_charts += 1;
+        chart_of[seed] = chart;
+        let mut avg = normal(&tris[seed]);
+        let mut stack = vec![seed];
+        while let Some(t) = stack.pop() {
+            for k in 0..3 {
+                let (a, b) = (tris[t][k], tris[t][(k + 1) % 3]);
+                let key = if a < b { (a, b) } else { (b, a) };
+                if let Some(adj) = edge_map.get(&key) {
+                    for &cand in adj {
+                        if chart_of[cand] != usize::MAX {
+                            continue;
+                        }
+                        let nrm = normal(&tris[cand]);
+                        // La distorsione aggiunta è limitata dalla deviazione angolare
+                        if nrm.dot(avg.normalize_or_zero()) >= cos_thr {
+                            chart_of[cand] = chart;
+                            avg += nrm;
+                            stack.push(cand);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    (chart_of, num_charts)
+}
+
+// Parametrizza un chart con una mappa conforme ai minimi quadrati (LSCM).
+// `chart_tris` sono triangoli in indici vertice globali; restituisce le UV per
+// ogni vertice locale e la lista dei vertici globali corrispondenti.
+fn lscm_parameterize(vertices: &[f32], chart_tris: &[[usize; 3]]) -> (Vec<[f32; 2]>, Vec<usize>) {
+    // Rimappa i vertici globali del chart su indici locali contigui
+    let mut local_of: std::collections::HashMap<usize, usize> = std::collections::HashMap::new();
+    let mut global: Vec<usize> = Vec::new();
+    for tri in chart_tris {
+        for &v in tri {
+            local_of.entry(v).or_insert_with(|| {
+                global.push(v);
+                global.len() - 1
+            });
+        }
+    }
+    let n = global.len();
+    if n < 3 {
+        // Chart degenere: collassa su UV nulle
+        return (vec![[0.0, 0.0]; n], global);
+    }
+
+    // Pinna i due vertici più lontani per fissare il gauge
+    let mut pa = 0usize;
+    let mut pb = 1usize;
+    let mut best = -1.0f32;
+    for (i, &gi) in global.iter().enumerate() {
+        let vi = vertex_at(vertices, gi);
+        for (j, &gj) in global.iter().enumerate().skip(i + 1) {
+            let d = (vi - vertex_at(vertices, gj)).length();
+            if d > best {
+                best = d;
+                pa = i;
+                pb = j;
+            }
+        }
+    }
+
+    let pinned = [pa, pb];
+    let pinned_uv = [Complex::new(0.0, 0.0), Complex::new(1.0, 0.0)];
+
+    // Mappa gli indici liberi in posizioni contigue nel sistema
+    let mut free_index = vec![usize::MAX; n];
+    let mut free_count = 0;
+    for (i, fi) in free_index.iter_mut().enumerate() {
+        if i != pa && i != pb {
+            *fi = free_count;
+            free_count += 1;
+        }
+    }
+
+    if free_count == 0 {
+        let mut uvs = vec![[0.0f32, 0.0]; n];
+        uvs[pa] = [0.0, 0.0];
+        uvs[pb] = [1.0, 0.0];
+        return (uvs, global);
+    }
+
+    // Equazioni normali complesse: (MᴴM) x = Mᴴ b, con M riga per triangolo
+    let mut ata = vec![vec![Complex::zero(); free_count]; free_count];
+    let mut atb = vec![Complex::zero(); free_count];
+
+    for tri in chart_tris {
+        let l = [local_of[&tri[0]], local_of[&tri[1]], local_of[&tri[2]]];
+        let p0 = vertex_at(vertices, tri[0]);
+        let p1 = vertex_at(vertices, tri[1]);
+        let p2 = vertex_at(vertices, tri[2]);
+
+        // Sistema di coordinate locale isometrico del triangolo
+        let e1 = p1 - p0;
+        let len1 = e1.length();
+        if len1 < 1e-12 {
+            continue;
+        }
+        let x_axis = e1 / len1;
+        let nrm = e1.cross(p2 - p0);
+        if nrm.length() < 1e-12 {
+            continue;
+        }
+        let y_axis = nrm.normalize().cross(x_axis);
+        let q0 = (0.0f64, 0.0f64);
+        let q1 = (len1 as f64, 0.0f64);
+        let d = p2 - p0;
+        let q2 = (d.dot(x_axis) as f64, d.dot(y_axis) as f64);
+
+        let area2 = q1.0 * q2.1 - q1.1 * q2.0;
+        if area2.abs() < 1e-12 {
+            continue;
+        }
+        let scale = 1.0 / area2.sqrt();
+
+        // Coefficienti complessi di conformalità per i tre vertici
+        let w = [
+            Complex::new((q2.0 - q1.0) * scale, (q2.1 - q1.1) * scale),
+            Complex::new((q0.0 - q2.0) * scale, (q0.1 - q2.1) * scale),
+            Complex::new((q1.0 - q0.0) * scale, (q1.1 - q0.1) * scale),
+        ];
+
+        // Accumula nelle equazioni normali, spostando i termini pinnati a destra
+        for a in 0..3 {
+            if free_index[l[a]] == usize::MAX {
+                continue;
+            }
+            let ra = free_index[l[a]];
+            // Contributo al lato destro dai vertici pinnati
+            for (pi, &pl) in pinned.iter().enumerate() {
+                for b in 0..3 {
+                    if l[b] == pl {
+                        let contrib = w[a].conj().mul(w[b]).mul(pinned_uv[pi]);
+                        atb[ra] = atb[ra].sub(contrib);
+                    }
+                }
+            }
+            // Contributo alla matrice dai vertici liberi
+            for b in 0..3 {
+                if free_index[l[b]] == usize::MAX {
+                    continue;
+                }
+                let rb = free_index[l[b]];
+                ata[ra][rb] = ata[ra][rb].add(w[a].conj().mul(w[b]));
+            }
+        }
+    }
+
+    // Risolvi il sistema complesso con eliminazione di Gauss e pivot parziale
+    let x = solve_complex(&mut ata, &mut atb);
+
+    let mut uvs = vec![[0.0f32, 0.0]; n];
+    uvs[pa] = [0.0, 0.0];
+    uvs[pb] = [1.0, 0.0];
+    for i in 0..n {
+        if free_index[i] != usize::MAX {
+            let c = x[free_index[i]];
+            uvs[i] = [c.re as f32, c.im as f32];
+        }
+    }
+    (uvs, global)
+}
+
+// Eliminazione di Gauss complessa con pivot parziale
+fn solve_complex(a: &mut [Vec<Complex>], b: &mut [Complex]) -> Vec<Complex> {
+    let n = b.len();
+    for col in 0..n {
+        // Pivot
+        let mut piv = col;
+        let mut best = a[col][col].norm_sq();
+        for (r, row) in a.iter().enumerate().skip(col + 1) {
+            let v = row[col].norm_sq();
+            if v > best {
+                best = v;
+                piv = r;
+            }
+        }
+        if best < 1e-18 {
+            continue;
+        }
+        a.swap(col, piv);
+        b.swap(col, piv);
+
+        let pivot_row = a[col].clone();
+        let diag = pivot_row[col];
+        for (r, row) in a.iter_mut().enumerate() {
+            if r == col {
+                continue;
+            }
+            let factor = row[col].div(diag);
+            for (rc, &pc) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+                let d = factor.mul(pc);
+                *rc = rc.sub(d);
+            }
+            let d = factor.mul(b[col]);
+            b[r] = b[r].sub(d);
+        }
+    }
+
+    let mut x = vec![Complex::zero(); n];
+    for (i, xi) in x.iter_mut().enumerate() {
+        if a[i][i].norm_sq() > 1e-18 {
+            *xi = b[i].div(a[i][i]);
+        }
+    }
+    x
+}
+
+// Rettangolo di un chart pronto per il packing
+struct ChartLayout {
+    local_verts: Vec<usize>, // vertici globali
+    uvs: Vec<[f32; 2]>,      // UV normalizzate a partire da (0,0)
+    width: f32,
+    height: f32,
+    x: u32,
+    y: u32,
+    rotated: bool,
+}
+
+// Impacchetta i chart nell'atlante con un posizionamento best-fit in basso a
+// sinistra su una griglia rasterizzata, con rotazione opzionale a 90°.
+fn pack_charts(charts: &mut [ChartLayout], resolution: u32, padding: u32, allow_rotation: bool) -> (u32, u32) {
+    // Densità comune di texel: adatta l'area totale alla risoluzione richiesta
+    let mut total_area = 0.0f32;
+    for c in charts.iter() {
+        total_area += (c.width * c.height).max(1e-6);
+    }
+    let usable = (resolution as f32 * 0.95).max(1.0);
+    let density = if total_area > 0.0 {
+        (usable * usable / (total_area * 1.3)).sqrt()
+    } else {
+        1.0
+    };
+
+    // Ordina i chart per lato maggiore decrescente per un packing più compatto
+    let mut order: Vec<usize> = (0..charts.len()).collect();
+    order.sort_by(|&i, &j| {
+        let hi = charts[i].height.max(charts[i].width);
+        let hj = charts[j].height.max(charts[j].width);
+        hj.partial_cmp(&hi).unwrap_or(Ordering::Equal)
+    });
+
+    // Occupazione su griglia a passo unitario di texel
+    let grid = resolution as usize;
+    let mut occupied = vec![false; grid * grid];
+    let pad = padding as usize;
+    let mut used_w = 0u32;
+    let mut used_h = 0u32;
+
+    let fits = |occ: &[bool], x: usize, y: usize, w: usize, h: usize, grid: usize| -> bool {
+        if x + w + 1 > grid || y + h + 1 > grid {
+            return false;
+        }
+        for yy in y..(y + h) {
+            for xx in x..(x + w) {
+                if occ[yy * grid + xx] {
+                    return false;
+                }
+            }
+        }
+        true
+    };
+
+    for &ci in &order {
+        let w0 = ((charts[ci].width * density).ceil() as usize + pad).max(1);
+        let h0 = ((charts[ci].height * density).ceil() as usize + pad).max(1);
+
+        // Scansione best-fit in basso a sinistra, con rotazione opzionale
+        let mut placed = false;
+        'scan: for y in 0..grid {
+            for x in 0..grid {
+                if fits(&occupied, x, y, w0, h0, grid) {
+                    for yy in y..(y + h0) {
+                        for xx in x..(x + w0) {
+                            occupied[yy * grid + xx] = true;
+                        }
+                    }
+                    charts[ci].x = x as u32;
+                    charts[ci].y = y as u32;
+                    charts[ci].rotated = false;
+                    used_w = used_w.max((x + w0) as u32);
+                    used_h = used_h.max((y + h0) as u32);
+                    placed = true;
+                    break 'scan;
+                }
+                if allow_rotation && fits(&occupied, x, y, h0, w0, grid) {
+                    for yy in y..(y + w0) {
+                        for xx in x..(x + h0) {
+                            occupied[yy * grid + xx] = true;
+                        }
+                    }
+                    charts[ci].x = x as u32;
+                    charts[ci].y = y as u32;
+                    charts[ci].rotated = true;
+                    used_w = used_w.max((x + h0) as u32);
+                    used_h = used_h.max((y + w0) as u32);
+                    placed = true;
+                    break 'scan;
+                }
+            }
+        }
+
+        // Se non entra, impila in cima mantenendo le coordinate valide
+        if !placed {
+            charts[ci].x = 0;
+            charts[ci].y = used_h;
+            charts[ci].rotated = false;
+            used_h += h0 as u32;
+            used_w = used_w.max(w0 as u32);
+        }
+
+        // Memorizza le dimensioni in texel per la scrittura finale
+        charts[ci].width = w0 as f32;
+        charts[ci].height = h0 as f32;
+    }
+
+    (used_w.max(1), used_h.max(1))
+}
+
+// Calcola l'atlante UV completo per una mesh arbitraria
+fn generate_uv_atlas_impl(vertices: &[f32], indices: &[u32], options: &UvAtlasOptions) -> UvAtlasResult {
+    let tcount = indices.len() / 3;
+    if tcount == 0 {
+        return UvAtlasResult { uvs: Vec::new(), width: 0, height: 0, vertex_remap: Vec::new() };
+    }
+
+    let (chart_of, num_charts) = segment_charts(vertices, indices, options.angle_threshold);
+
+    // Raggruppa i triangoli per chart
+    let mut chart_tris: Vec<Vec<[usize; 3]>> = vec![Vec::new(); num_charts];
+    for t in 0..tcount {
+        let tri = [indices[t * 3] as usize, indices[t * 3 + 1] as usize, indices[t * 3 + 2] as usize];
+        chart_tris[chart_of[t]].push(tri);
+    }
+
+    // Parametrizza ogni chart e normalizza le UV nel suo bounding box
+    let mut charts: Vec<ChartLayout> = Vec::new();
+    for tris in &chart_tris {
+        if tris.is_empty() {
+            continue;
+        }
+        let (mut uvs, global) = lscm_parameterize(vertices, tris);
+        let mut min = [f32::MAX, f32::MAX];
+        let mut max = [f32::MIN, f32::MIN];
+        for uv in &uvs {
+            min[0] = min[0].min(uv[0]);
+            min[1] = min[1].min(uv[1]);
+            max[0] = max[0].max(uv[0]);
+            max[1] = max[1].max(uv[1]);
+        }
+        for uv in &mut uvs {
+            uv[0] -= min[0];
+            uv[1] -= min[1];
+        }
+        charts.push(ChartLayout {
+            local_verts: global,
+            uvs,
+            width: (max[0] - min[0]).max(1e-4),
+            height: (max[1] - min[1]).max(1e-4),
+            x: 0,
+            y: 0,
+            rotated: false,
+        });
+    }
+
+    // Salva le dimensioni in unità UV prima del packing (che le sovrascrive)
+    let chart_uv_size: Vec<(f32, f32)> = charts.iter().map(|c| (c.width, c.height)).collect();
+
+    let (atlas_w, atlas_h) = pack_charts(&mut charts, options.resolution, options.padding, options.allow_rotation);
+
+    // Costruisce le UV finali con split dei vertici lungo le cuciture dei chart
+    let mut final_uvs: Vec<f32> = Vec::new();
+    let mut vertex_remap: Vec<u32> = Vec::new();
+    let pad = options.padding as f32;
+
+    for (ci, chart) in charts.iter().enumerate() {
+        let (uw, uh) = chart_uv_size[ci];
+        for (li, &g) in chart.local_verts.iter().enumerate() {
+            // UV locale normalizzata in [0,1] entro il chart
+            let mut u = chart.uvs[li][0] / uw;
+            let mut v = chart.uvs[li][1] / uh;
+            // Dimensione del chart in texel (senza padding)
+            let (cw, ch) = if chart.rotated {
+                (chart.height - pad, chart.width - pad)
+            } else {
+                (chart.width - pad, chart.height - pad)
+            };
+            if chart.rotated {
+                std::mem::swap(&mut u, &mut v);
+            }
+            let px = chart.x as f32 + u * cw.max(1.0);
+            let py = chart.y as f32 + v * ch.max(1.0);
+            // Normalizza nell'atlante effettivamente usato
+            final_uvs.push(px / atlas_w as f32);
+            final_uvs.push(py / atlas_h as f32);
+            vertex_remap.push(g as u32);
+        }
+    }
+
+    UvAtlasResult {
+        uvs: final_uvs,
+        width: atlas_w,
+        height: atlas_h,
+        vertex_remap,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Involucro convesso incrementale (QuickHull 3D)
+// ---------------------------------------------------------------------------
+
+// Faccia dell'involucro con normale orientata verso l'esterno e punti esterni
+struct HullFace {
+    v: [usize; 3],
+    normal: Vec3,
+    anchor: Vec3,
+    outside: Vec<usize>,
+}
+
+impl HullFace {
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        self.normal.dot(p - self.anchor)
+    }
+}
+
+// Crea una faccia orientata in modo che la normale punti lontano da `interior`
+fn make_hull_face(points: &[Vec3], a: usize, b: usize, c: usize, interior: Vec3) -> HullFace {
+    let (pa, pb, pc) = (points[a], points[b], points[c]);
+    let mut normal = (pb - pa).cross(pc - pa).normalize_or_zero();
+    let mut v = [a, b, c];
+    if normal.dot(interior - pa) > 0.0 {
+        normal = -normal;
+        v = [a, c, b];
+    }
+    HullFace { v, normal, anchor: pa, outside: Vec::new() }
+}
+
+fn quickhull(vertices: &[f32], max_vertices: u32) -> MeshData {
+    let points: Vec<Vec3> = (0..vertices.len() / 3).map(|i| vertex_at(vertices, i)).collect();
+    let n = points.len();
+    if n < 4 {
+        return MeshData { vertices: vertices.to_vec(), indices: Vec::new() };
+    }
+
+    // Scala ed epsilon relativi all'estensione della nuvola
+    let mut min = points[0];
+    let mut max = points[0];
+    for p in &points {
+        min = min.min(*p);
+        max = max.max(*p);
+    }
+    let scale = (max - min).length().max(1e-6);
+    let eps = 1e-6 * scale;
+
+    // Tetraedro iniziale da 4 punti estremi non complanari
+    let mut i0 = 0;
+    let mut i1 = 0;
+    let mut best = -1.0f32;
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let d = (points[i] - points[j]).length();
+            if d > best {
+                best = d;
+                i0 = i;
+                i1 = j;
+            }
+        }
+    }
+    if best < eps {
+        return MeshData { vertices: vertices.to_vec(), indices: Vec::new() };
+    }
+
+    // Punto più lontano dalla retta i0-i1
+    let line = (points[i1] - points[i0]).normalize_or_zero();
+    let mut i2 = 0;
+    best = -1.0;
+    for i in 0..n {
+        let w = points[i] - points[i0];
+        let d = (w - line * w.dot(line)).length();
+        if d > best {
+            best = d;
+            i2 = i;
+        }
+    }
+    if best < eps {
+        return MeshData { vertices: vertices.to_vec(), indices: Vec::new() };
+    }
+
+    // Punto più lontano dal piano i0-i1-i2
+    let pn = (points[i1] - points[i0]).cross(points[i2] - points[i0]).normalize_or_zero();
+    let mut i3 = 0;
+    best = -1.0;
+    for i in 0..n {
+        let d = pn.dot(points[i] - points[i0]).abs();
+        if d > best {
+            best = d;
+            i3 = i;
+        }
+    }
+    if best < eps {
+        return MeshData { vertices: vertices.to_vec(), indices: Vec::new() };
+    }
+
+    let interior = (points[i0] + points[i1] + points[i2] + points[i3]) * 0.25;
+    let mut faces = vec![
+        make_hull_face(&points, i0, i1, i2, interior),
+        make_hull_face(&points, i0, i1, i3, interior),
+        make_hull_face(&points, i0, i2, i3, interior),
+        make_hull_face(&points, i1, i2, i3, interior),
+    ];
+
+    // Assegna ogni punto rimanente alla faccia sopra cui si trova
+    let seeds = [i0, i1, i2, i3];
+    for (i, &p) in points.iter().enumerate() {
+        if seeds.contains(&i) {
+            continue;
+        }
+        let mut bf = usize::MAX;
+        let mut bd = eps;
+        for (fi, f) in faces.iter().enumerate() {
+            let d = f.signed_distance(p);
+            if d > bd {
+                bd = d;
+                bf = fi;
+            }
+        }
+        if bf != usize::MAX {
+            faces[bf].outside.push(i);
+        }
+    }
+
+    // Conta i vertici distinti già presenti sull'involucro
+    let distinct_count = |faces: &[HullFace]| -> usize {
+        let mut set = std::collections::HashSet::new();
+        for f in faces {
+            for &v in &f.v {
+                set.insert(v);
+            }
+        }
+        set.len()
+    };
+
+    loop {
+        // Scegli la faccia con punti esterni e il suo punto più lontano (occhio)
+        let mut fi = usize::MAX;
+        let mut eye = usize::MAX;
+        let mut bd = eps;
+        for (idx, f) in faces.iter().enumerate() {
+            for &p in &f.outside {
+                let d = f.signed_distance(points[p]);
+                if d > bd {
+                    bd = d;
+                    fi = idx;
+                    eye = p;
+                }
+            }
+        }
+        if fi == usize::MAX {
+            break;
+        }
+
+        // Rispetta il limite di vertici scartando i punti residui
+        if max_vertices > 0 && distinct_count(&faces) >= max_vertices as usize {
+            break;
+        }
+
+        let eye_p = points[eye];
+
+        // Facce visibili dall'occhio
+        let mut visible = vec![false; faces.len()];
+        for (idx, f) in faces.iter().enumerate() {
+            if f.signed_distance(eye_p) > eps {
+                visible[idx] = true;
+            }
+        }
+
+        // Archi di orizzonte: archi presenti in una sola faccia visibile
+        let mut edge_count: std::collections::HashMap<(usize, usize), u32> = std::collections::HashMap::new();
+        for (idx, f) in faces.iter().enumerate() {
+            if !visible[idx] {
+                continue;
+            }
+            for k in 0..3 {
+                let (a, b) = (f.v[k], f.v[(k + 1) % 3]);
+                let key = if a < b { (a, b) } else { (b, a) };
+                *edge_count.entry(key).or_insert(0) += 1;
+            }
+        }
+        let horizon: Vec<(usize, usize)> = edge_count
+            .iter()
+            .filter(|(_, &c)| c == 1)
+            .map(|(&e, _)| e)
+            .collect();
+
+        // Raccogli i punti orfani dalle facce visibili
+        let mut orphans: Vec<usize> = Vec::new();
+        for (idx, f) in faces.iter().enumerate() {
+            if visible[idx] {
+                for &p in &f.outside {
+                    if p != eye {
+                        orphans.push(p);
+                    }
+                }
+            }
+        }
+
+        // Rimuovi le facce visibili
+        let mut kept: Vec<HullFace> = Vec::new();
+        for (idx, f) in faces.into_iter().enumerate() {
+            if !visible[idx] {
+                kept.push(f);
+            }
+        }
+        faces = kept;
+
+        // Crea nuove facce collegando l'occhio a ogni arco di orizzonte
+        let new_start = faces.len();
+        for (a, b) in horizon {
+            faces.push(make_hull_face(&points, a, b, eye, interior));
+        }
+
+        // Riassegna gli orfani alle nuove facce
+        for p in orphans {
+            if p == eye {
+                continue;
+            }
+            let mut best_idx = usize::MAX;
+            let mut best_d = eps;
+            for (idx, f) in faces.iter().enumerate().skip(new_start) {
+                let d = f.signed_distance(points[p]);
+                if d > best_d {
+                    best_d = d;
+                    best_idx = idx;
+                }
+            }
+            if best_idx != usize::MAX {
+                faces[best_idx].outside.push(p);
+            }
+        }
+    }
+
+    // Emetti le facce come triangoli con vertici compattati
+    let mut remap = vec![u32::MAX; n];
+    let mut out_vertices: Vec<f32> = Vec::new();
+    let mut out_indices: Vec<u32> = Vec::new();
+    for f in &faces {
+        for &v in &f.v {
+            if remap[v] == u32::MAX {
+                remap[v] = (out_vertices.len() / 3) as u32;
+                out_vertices.push(points[v].x);
+                out_vertices.push(points[v].y);
+                out_vertices.push(points[v].z);
+            }
+            out_indices.push(remap[v]);
+        }
+    }
+
+    MeshData { vertices: out_vertices, indices: out_indices }
+}
+
+// ---------------------------------------------------------------------------
+// Estrazione di isosuperficie (marching cubes) ed esportazione STL binaria
+// ---------------------------------------------------------------------------
+
+// Tabella degli archi attivi per ciascuna delle 256 configurazioni di celle
+#[rustfmt::skip]
+const MC_EDGE_TABLE: [u16; 256] = [
+    0x0, 0x109, 0x203, 0x30a, 0x406, 0x50f, 0x605, 0x70c,
+    0x80c, 0x905, 0xa0f, 0xb06, 0xc0a, 0xd03, 0xe09, 0xf00,
+    0x190, 0x99, 0x393, 0x29a, 0x596, 0x49f, 0x795, 0x69c,
+    0x99c, 0x895, 0xb9f, 0xa96, 0xd9a, 0xc93, 0xf99, 0xe90,
+    0x230, 0x339, 0x33, 0x13a, 0x636, 0x73f, 0x435, 0x53c,
+    0xa3c, 0xb35, 0x83f, 0x936, 0xe3a, 0xf33, 0xc39, 0xd30,
+    0x3a0, 0x2a9, 0x1a3, 0xaa, 0x7a6, 0x6af, 0x5a5, 0x4ac,
+    0xbac, 0xaa5, 0x9af, 0x8a6, 0xfaa, 0xea3, 0xda9, 0xca0,
+    0x460, 0x569, 0x663, 0x76a, 0x66, 0x16f, 0x265, 0x36c,
+    0xc6c, 0xd65, 0xe6f, 0xf66, 0x86a, 0x963, 0xa69, 0xb60,
+    0x5f0, 0x4f9, 0x7f3, 0x6fa, 0x1f6, 0xff, 0x3f5, 0x2fc,
+    0xdfc, 0xcf5, 0xfff, 0xef6, 0x9fa, 0x8f3, 0xbf9, 0xaf0,
+    0x650, 0x759, 0x453, 0x55a, 0x256, 0x35f, 0x55, 0x15c,
+    0xe5c, 0xf55, 0xc5f, 0xd56, 0xa5a, 0xb53, 0x859, 0x950,
+    0x7c0, 0x6c9, 0x5c3, 0x4ca, 0x3c6, 0x2cf, 0x1c5, 0xcc,
+    0xfcc, 0xec5, 0xdcf, 0xcc6, 0xbca, 0xac3, 0x9c9, 0x8c0,
+    0x8c0, 0x9c9, 0xac3, 0xbca, 0xcc6, 0xdcf, 0xec5, 0xfcc,
+    0xcc, 0x1c5, 0x2cf, 0x3c6, 0x4ca, 0x5c3, 0x6c9, 0x7c0,
+    0x950, 0x859, 0xb53, 0xa5a, 0xd56, 0xc5f, 0xf55, 0xe5c,
+    0x15c, 0x55, 0x35f, 0x256, 0x55a, 0x453, 0x759, 0x650,
+    0xaf0, 0xbf9, 0x8f3, 0x9fa, 0xef6, 0xfff, 0xcf5, 0xdfc,
+    0x2fc, 0x3f5, 0xff, 0x1f6, 0x6fa, 0x7f3, 0x4f9, 0x5f0,
+    0xb60, 0xa69, 0x963, 0x86a, 0xf66, 0xe6f, 0xd65, 0xc6c,
+    0x36c, 0x265, 0x16f, 0x66, 0x76a, 0x663, 0x569, 0x460,
+    0xca0, 0xda9, 0xea3, 0xfaa, 0x8a6, 0x9af, 0xaa5, 0xbac,
+    0x4ac, 0x5a5, 0x6af, 0x7a6, 0xaa, 0x1a3, 0x2a9, 0x3a0,
+    0xd30, 0xc39, 0xf33, 0xe3a, 0x936, 0x83f, 0xb35, 0xa3c,
+    0x53c, 0x435, 0x73f, 0x636, 0x13a, 0x33, 0x339, 0x230,
+    0xe90, 0xf99, 0xc93, 0xd9a, 0xa96, 0xb9f, 0x895, 0x99c,
+    0x69c, 0x795, 0x49f, 0x596, 0x29a, 0x393, 0x99, 0x190,
+    0xf00, 0xe09, 0xd03, 0xc0a, 0xb06, 0xa0f, 0x905, 0x80c,
+    0x70c, 0x605, 0x50f, 0x406, 0x30a, 0x203, 0x109, 0x0,
+];
+
+// Tabella dei triangoli: fino a 5 triangoli per cella, terminati da -1
+#[rustfmt::skip]
+const MC_TRI_TABLE: [[i8; 16]; 256] = [
+    [-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,8,3,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,1,9,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,8,3,9,8,1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,2,10,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,8,3,1,2,10,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [9,2,10,0,2,9,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [2,8,3,2,10,8,10,9,8,-1,-1,-1,-1,-1,-1,-1],
+    [3,11,2,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,11,2,8,11,0,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,9,0,2,3,11,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,11,2,1,9,11,9,8,11,-1,-1,-1,-1,-1,-1,-1],
+    [3,10,1,11,10,3,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,10,1,0,8,10,8,11,10,-1,-1,-1,-1,-1,-1,-1],
+    [3,9,0,3,11,9,11,10,9,-1,-1,-1,-1,-1,-1,-1],
+    [9,8,10,10,8,11,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [4,7,8,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [4,3,0,7,3,4,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,1,9,8,4,7,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [4,1,9,4,7,1,7,3,1,-1,-1,-1,-1,-1,-1,-1],
+    [1,2,10,8,4,7,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [3,4,7,3,0,4,1,2,10,-1,-1,-1,-1,-1,-1,-1],
+    [9,2,10,9,0,2,8,4,7,-1,-1,-1,-1,-1,-1,-1],
+    [2,10,9,2,9,7,2,7,3,7,9,4,-1,-1,-1,-1],
+    [8,4,7,3,11,2,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [11,4,7,11,2,4,2,0,4,-1,-1,-1,-1,-1,-1,-1],
+    [9,0,1,8,4,7,2,3,11,-1,-1,-1,-1,-1,-1,-1],
+    [4,7,11,9,4,11,9,11,2,9,2,1,-1,-1,-1,-1],
+    [3,10,1,3,11,10,7,8,4,-1,-1,-1,-1,-1,-1,-1],
+    [1,11,10,1,4,11,1,0,4,7,11,4,-1,-1,-1,-1],
+    [4,7,8,9,0,11,9,11,10,11,0,3,-1,-1,-1,-1],
+    [4,7,11,4,11,9,9,11,10,-1,-1,-1,-1,-1,-1,-1],
+    [9,5,4,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [9,5,4,0,8,3,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,5,4,1,5,0,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [8,5,4,8,3,5,3,1,5,-1,-1,-1,-1,-1,-1,-1],
+    [1,2,10,9,5,4,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [3,0,8,1,2,10,4,9,5,-1,-1,-1,-1,-1,-1,-1],
+    [5,2,10,5,4,2,4,0,2,-1,-1,-1,-1,-1,-1,-1],
+    [2,10,5,3,2,5,3,5,4,3,4,8,-1,-1,-1,-1],
+    [9,5,4,2,3,11,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,11,2,0,8,11,4,9,5,-1,-1,-1,-1,-1,-1,-1],
+    [0,5,4,0,1,5,2,3,11,-1,-1,-1,-1,-1,-1,-1],
+    [2,1,5,2,5,8,2,8,11,4,8,5,-1,-1,-1,-1],
+    [10,3,11,10,1,3,9,5,4,-1,-1,-1,-1,-1,-1,-1],
+    [4,9,5,0,8,1,8,10,1,8,11,10,-1,-1,-1,-1],
+    [5,4,0,5,0,11,5,11,10,11,0,3,-1,-1,-1,-1],
+    [5,4,8,5,8,10,10,8,11,-1,-1,-1,-1,-1,-1,-1],
+    [9,7,8,5,7,9,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [9,3,0,9,5,3,5,7,3,-1,-1,-1,-1,-1,-1,-1],
+    [0,7,8,0,1,7,1,5,7,-1,-1,-1,-1,-1,-1,-1],
+    [1,5,3,3,5,7,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [9,7,8,9,5,7,10,1,2,-1,-1,-1,-1,-1,-1,-1],
+    [10,1,2,9,5,0,5,3,0,5,7,3,-1,-1,-1,-1],
+    [8,0,2,8,2,5,8,5,7,10,5,2,-1,-1,-1,-1],
+    [2,10,5,2,5,3,3,5,7,-1,-1,-1,-1,-1,-1,-1],
+    [7,9,5,7,8,9,3,11,2,-1,-1,-1,-1,-1,-1,-1],
+    [9,5,7,9,7,2,9,2,0,2,7,11,-1,-1,-1,-1],
+    [2,3,11,0,1,8,1,7,8,1,5,7,-1,-1,-1,-1],
+    [11,2,1,11,1,7,7,1,5,-1,-1,-1,-1,-1,-1,-1],
+    [9,5,8,8,5,7,10,1,3,10,3,11,-1,-1,-1,-1],
+    [5,7,0,5,0,9,7,11,0,1,0,10,11,10,0,-1],
+    [11,10,0,11,0,3,10,5,0,8,0,7,5,7,0,-1],
+    [11,10,5,7,11,5,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [10,6,5,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,8,3,5,10,6,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [9,0,1,5,10,6,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,8,3,1,9,8,5,10,6,-1,-1,-1,-1,-1,-1,-1],
+    [1,6,5,2,6,1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,6,5,1,2,6,3,0,8,-1,-1,-1,-1,-1,-1,-1],
+    [9,6,5,9,0,6,0,2,6,-1,-1,-1,-1,-1,-1,-1],
+    [5,9,8,5,8,2,5,2,6,3,2,8,-1,-1,-1,-1],
+    [2,3,11,10,6,5,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [11,0,8,11,2,0,10,6,5,-1,-1,-1,-1,-1,-1,-1],
+    [0,1,9,2,3,11,5,10,6,-1,-1,-1,-1,-1,-1,-1],
+    [5,10,6,1,9,2,9,11,2,9,8,11,-1,-1,-1,-1],
+    [6,3,11,6,5,3,5,1,3,-1,-1,-1,-1,-1,-1,-1],
+    [0,8,11,0,11,5,0,5,1,5,11,6,-1,-1,-1,-1],
+    [3,11,6,0,3,6,0,6,5,0,5,9,-1,-1,-1,-1],
+    [6,5,9,6,9,11,11,9,8,-1,-1,-1,-1,-1,-1,-1],
+    [5,10,6,4,7,8,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [4,3,0,4,7,3,6,5,10,-1,-1,-1,-1,-1,-1,-1],
+    [1,9,0,5,10,6,8,4,7,-1,-1,-1,-1,-1,-1,-1],
+    [10,6,5,1,9,7,1,7,3,7,9,4,-1,-1,-1,-1],
+    [6,1,2,6,5,1,4,7,8,-1,-1,-1,-1,-1,-1,-1],
+    [1,2,5,5,2,6,3,0,4,3,4,7,-1,-1,-1,-1],
+    [8,4,7,9,0,5,0,6,5,0,2,6,-1,-1,-1,-1],
+    [7,3,9,7,9,4,3,2,9,5,9,6,2,6,9,-1],
+    [3,11,2,7,8,4,10,6,5,-1,-1,-1,-1,-1,-1,-1],
+    [5,10,6,4,7,2,4,2,0,2,7,11,-1,-1,-1,-1],
+    [0,1,9,4,7,8,2,3,11,5,10,6,-1,-1,-1,-1],
+    [9,2,1,9,11,2,9,4,11,7,11,4,5,10,6,-1],
+    [8,4,7,3,11,5,3,5,1,5,11,6,-1,-1,-1,-1],
+    [5,1,11,5,11,6,1,0,11,7,11,4,0,4,11,-1],
+    [0,5,9,0,6,5,0,3,6,11,6,3,8,4,7,-1],
+    [6,5,9,6,9,11,4,7,9,7,11,9,-1,-1,-1,-1],
+    [10,4,9,6,4,10,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [4,10,6,4,9,10,0,8,3,-1,-1,-1,-1,-1,-1,-1],
+    [10,0,1,10,6,0,6,4,0,-1,-1,-1,-1,-1,-1,-1],
+    [8,3,1,8,1,6,8,6,4,6,1,10,-1,-1,-1,-1],
+    [1,4,9,1,2,4,2,6,4,-1,-1,-1,-1,-1,-1,-1],
+    [3,0,8,1,2,9,2,4,9,2,6,4,-1,-1,-1,-1],
+    [0,2,4,4,2,6,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [8,3,2,8,2,4,4,2,6,-1,-1,-1,-1,-1,-1,-1],
+    [10,4,9,10,6,4,11,2,3,-1,-1,-1,-1,-1,-1,-1],
+    [0,8,2,2,8,11,4,9,10,4,10,6,-1,-1,-1,-1],
+    [3,11,2,0,1,6,0,6,4,6,1,10,-1,-1,-1,-1],
+    [6,4,1,6,1,10,4,8,1,2,1,11,8,11,1,-1],
+    [9,6,4,9,3,6,9,1,3,11,6,3,-1,-1,-1,-1],
+    [8,11,1,8,1,0,11,6,1,9,1,4,6,4,1,-1],
+    [3,11,6,3,6,0,0,6,4,-1,-1,-1,-1,-1,-1,-1],
+    [6,4,8,11,6,8,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [7,10,6,7,8,10,8,9,10,-1,-1,-1,-1,-1,-1,-1],
+    [0,7,3,0,10,7,0,9,10,6,7,10,-1,-1,-1,-1],
+    [10,6,7,1,10,7,1,7,8,1,8,0,-1,-1,-1,-1],
+    [10,6,7,10,7,1,1,7,3,-1,-1,-1,-1,-1,-1,-1],
+    [1,2,6,1,6,8,1,8,9,8,6,7,-1,-1,-1,-1],
+    [2,6,9,2,9,1,6,7,9,0,9,3,7,3,9,-1],
+    [7,8,0,7,0,6,6,0,2,-1,-1,-1,-1,-1,-1,-1],
+    [7,3,2,6,7,2,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [2,3,11,10,6,8,10,8,9,8,6,7,-1,-1,-1,-1],
+    [2,0,7,2,7,11,0,9,7,6,7,10,9,10,7,-1],
+    [1,8,0,1,7,8,1,10,7,6,7,10,2,3,11,-1],
+    [11,2,1,11,1,7,10,6,1,6,7,1,-1,-1,-1,-1],
+    [8,9,6,8,6,7,9,1,6,11,6,3,1,3,6,-1],
+    [0,9,1,11,6,7,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [7,8,0,7,0,6,3,11,0,11,6,0,-1,-1,-1,-1],
+    [7,11,6,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [7,6,11,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [3,0,8,11,7,6,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,1,9,11,7,6,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [8,1,9,8,3,1,11,7,6,-1,-1,-1,-1,-1,-1,-1],
+    [10,1,2,6,11,7,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,2,10,3,0,8,6,11,7,-1,-1,-1,-1,-1,-1,-1],
+    [2,9,0,2,10,9,6,11,7,-1,-1,-1,-1,-1,-1,-1],
+    [6,11,7,2,10,3,10,8,3,10,9,8,-1,-1,-1,-1],
+    [7,2,3,6,2,7,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [7,0,8,7,6,0,6,2,0,-1,-1,-1,-1,-1,-1,-1],
+    [2,7,6,2,3,7,0,1,9,-1,-1,-1,-1,-1,-1,-1],
+    [1,6,2,1,8,6,1,9,8,8,7,6,-1,-1,-1,-1],
+    [10,7,6,10,1,7,1,3,7,-1,-1,-1,-1,-1,-1,-1],
+    [10,7,6,1,7,10,1,8,7,1,0,8,-1,-1,-1,-1],
+    [0,3,7,0,7,10,0,10,9,6,10,7,-1,-1,-1,-1],
+    [7,6,10,7,10,8,8,10,9,-1,-1,-1,-1,-1,-1,-1],
+    [6,8,4,11,8,6,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [3,6,11,3,0,6,0,4,6,-1,-1,-1,-1,-1,-1,-1],
+    [8,6,11,8,4,6,9,0,1,-1,-1,-1,-1,-1,-1,-1],
+    [9,4,6,9,6,3,9,3,1,11,3,6,-1,-1,-1,-1],
+    [6,8,4,6,11,8,2,10,1,-1,-1,-1,-1,-1,-1,-1],
+    [1,2,10,3,0,11,0,6,11,0,4,6,-1,-1,-1,-1],
+    [4,11,8,4,6,11,0,2,9,2,10,9,-1,-1,-1,-1],
+    [10,9,3,10,3,2,9,4,3,11,3,6,4,6,3,-1],
+    [8,2,3,8,4,2,4,6,2,-1,-1,-1,-1,-1,-1,-1],
+    [0,4,2,4,6,2,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,9,0,2,3,4,2,4,6,4,3,8,-1,-1,-1,-1],
+    [1,9,4,1,4,2,2,4,6,-1,-1,-1,-1,-1,-1,-1],
+    [8,1,3,8,6,1,8,4,6,6,10,1,-1,-1,-1,-1],
+    [10,1,0,10,0,6,6,0,4,-1,-1,-1,-1,-1,-1,-1],
+    [4,6,3,4,3,8,6,10,3,0,3,9,10,9,3,-1],
+    [10,9,4,6,10,4,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [4,9,5,7,6,11,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,8,3,4,9,5,11,7,6,-1,-1,-1,-1,-1,-1,-1],
+    [5,0,1,5,4,0,7,6,11,-1,-1,-1,-1,-1,-1,-1],
+    [11,7,6,8,3,4,3,5,4,3,1,5,-1,-1,-1,-1],
+    [9,5,4,10,1,2,7,6,11,-1,-1,-1,-1,-1,-1,-1],
+    [6,11,7,1,2,10,0,8,3,4,9,5,-1,-1,-1,-1],
+    [7,6,11,5,4,10,4,2,10,4,0,2,-1,-1,-1,-1],
+    [3,4,8,3,5,4,3,2,5,10,5,2,11,7,6,-1],
+    [7,2,3,7,6,2,5,4,9,-1,-1,-1,-1,-1,-1,-1],
+    [9,5,4,0,8,6,0,6,2,6,8,7,-1,-1,-1,-1],
+    [3,6,2,3,7,6,1,5,0,5,4,0,-1,-1,-1,-1],
+    [6,2,8,6,8,7,2,1,8,4,8,5,1,5,8,-1],
+    [9,5,4,10,1,6,1,7,6,1,3,7,-1,-1,-1,-1],
+    [1,6,10,1,7,6,1,0,7,8,7,0,9,5,4,-1],
+    [4,0,10,4,10,5,0,3,10,6,10,7,3,7,10,-1],
+    [7,6,10,7,10,8,5,4,10,4,8,10,-1,-1,-1,-1],
+    [6,9,5,6,11,9,11,8,9,-1,-1,-1,-1,-1,-1,-1],
+    [3,6,11,0,6,3,0,5,6,0,9,5,-1,-1,-1,-1],
+    [0,11,8,0,5,11,0,1,5,5,6,11,-1,-1,-1,-1],
+    [6,11,3,6,3,5,5,3,1,-1,-1,-1,-1,-1,-1,-1],
+    [1,2,10,9,5,11,9,11,8,11,5,6,-1,-1,-1,-1],
+    [0,11,3,0,6,11,0,9,6,5,6,9,1,2,10,-1],
+    [11,8,5,11,5,6,8,0,5,10,5,2,0,2,5,-1],
+    [6,11,3,6,3,5,2,10,3,10,5,3,-1,-1,-1,-1],
+    [5,8,9,5,2,8,5,6,2,3,8,2,-1,-1,-1,-1],
+    [9,5,6,9,6,0,0,6,2,-1,-1,-1,-1,-1,-1,-1],
+    [1,5,8,1,8,0,5,6,8,3,8,2,6,2,8,-1],
+    [1,5,6,2,1,6,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,3,6,1,6,10,3,8,6,5,6,9,8,9,6,-1],
+    [10,1,0,10,0,6,9,5,0,5,6,0,-1,-1,-1,-1],
+    [0,3,8,5,6,10,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [10,5,6,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [11,5,10,7,5,11,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [11,5,10,11,7,5,8,3,0,-1,-1,-1,-1,-1,-1,-1],
+    [5,11,7,5,10,11,1,9,0,-1,-1,-1,-1,-1,-1,-1],
+    [10,7,5,10,11,7,9,8,1,8,3,1,-1,-1,-1,-1],
+    [11,1,2,11,7,1,7,5,1,-1,-1,-1,-1,-1,-1,-1],
+    [0,8,3,1,2,7,1,7,5,7,2,11,-1,-1,-1,-1],
+    [9,7,5,9,2,7,9,0,2,2,11,7,-1,-1,-1,-1],
+    [7,5,2,7,2,11,5,9,2,3,2,8,9,8,2,-1],
+    [2,5,10,2,3,5,3,7,5,-1,-1,-1,-1,-1,-1,-1],
+    [8,2,0,8,5,2,8,7,5,10,2,5,-1,-1,-1,-1],
+    [9,0,1,5,10,3,5,3,7,3,10,2,-1,-1,-1,-1],
+    [9,8,2,9,2,1,8,7,2,10,2,5,7,5,2,-1],
+    [1,3,5,3,7,5,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,8,7,0,7,1,1,7,5,-1,-1,-1,-1,-1,-1,-1],
+    [9,0,3,9,3,5,5,3,7,-1,-1,-1,-1,-1,-1,-1],
+    [9,8,7,5,9,7,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [5,8,4,5,10,8,10,11,8,-1,-1,-1,-1,-1,-1,-1],
+    [5,0,4,5,11,0,5,10,11,11,3,0,-1,-1,-1,-1],
+    [0,1,9,8,4,10,8,10,11,10,4,5,-1,-1,-1,-1],
+    [10,11,4,10,4,5,11,3,4,9,4,1,3,1,4,-1],
+    [2,5,1,2,8,5,2,11,8,4,5,8,-1,-1,-1,-1],
+    [0,4,11,0,11,3,4,5,11,2,11,1,5,1,11,-1],
+    [0,2,5,0,5,9,2,11,5,4,5,8,11,8,5,-1],
+    [9,4,5,2,11,3,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [2,5,10,3,5,2,3,4,5,3,8,4,-1,-1,-1,-1],
+    [5,10,2,5,2,4,4,2,0,-1,-1,-1,-1,-1,-1,-1],
+    [3,10,2,3,5,10,3,8,5,4,5,8,0,1,9,-1],
+    [5,10,2,5,2,4,1,9,2,9,4,2,-1,-1,-1,-1],
+    [8,4,5,8,5,3,3,5,1,-1,-1,-1,-1,-1,-1,-1],
+    [0,4,5,1,0,5,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [8,4,5,8,5,3,9,0,5,0,3,5,-1,-1,-1,-1],
+    [9,4,5,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [4,11,7,4,9,11,9,10,11,-1,-1,-1,-1,-1,-1,-1],
+    [0,8,3,4,9,7,9,11,7,9,10,11,-1,-1,-1,-1],
+    [1,10,11,1,11,4,1,4,0,7,4,11,-1,-1,-1,-1],
+    [3,1,4,3,4,8,1,10,4,7,4,11,10,11,4,-1],
+    [4,11,7,9,11,4,9,2,11,9,1,2,-1,-1,-1,-1],
+    [9,7,4,9,11,7,9,1,11,2,11,1,0,8,3,-1],
+    [11,7,4,11,4,2,2,4,0,-1,-1,-1,-1,-1,-1,-1],
+    [11,7,4,11,4,2,8,3,4,3,2,4,-1,-1,-1,-1],
+    [2,9,10,2,7,9,2,3,7,7,4,9,-1,-1,-1,-1],
+    [9,10,7,9,7,4,10,2,7,8,7,0,2,0,7,-1],
+    [3,7,10,3,10,2,7,4,10,1,10,0,4,0,10,-1],
+    [1,10,2,8,7,4,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [4,9,1,4,1,7,7,1,3,-1,-1,-1,-1,-1,-1,-1],
+    [4,9,1,4,1,7,0,8,1,8,7,1,-1,-1,-1,-1],
+    [4,0,3,7,4,3,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [4,8,7,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [9,10,8,10,11,8,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [3,0,9,3,9,11,11,9,10,-1,-1,-1,-1,-1,-1,-1],
+    [0,1,10,0,10,8,8,10,11,-1,-1,-1,-1,-1,-1,-1],
+    [3,1,10,11,3,10,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,2,11,1,11,9,9,11,8,-1,-1,-1,-1,-1,-1,-1],
+    [3,0,9,3,9,11,1,2,9,2,11,9,-1,-1,-1,-1],
+    [0,2,11,8,0,11,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [3,2,11,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [2,3,8,2,8,10,10,8,9,-1,-1,-1,-1,-1,-1,-1],
+    [9,10,2,0,9,2,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [2,3,8,2,8,10,0,1,8,1,10,8,-1,-1,-1,-1],
+    [1,10,2,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [1,3,8,9,1,8,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,9,1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [0,3,8,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+    [-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1,-1],
+];
+
+// Angoli e archi standard della cella del marching cubes
+const MC_CORNERS: [[u32; 3]; 8] = [
+    [0, 0, 0], [1, 0, 0], [1, 0, 1], [0, 0, 1],
+    [0, 1, 0], [1, 1, 0], [1, 1, 1], [0, 1, 1],
+];
+const MC_EDGE_CORNERS: [[usize; 2]; 12] = [
+    [0, 1], [1, 2], [2, 3], [3, 0],
+    [4, 5], [5, 6], [6, 7], [7, 4],
+    [0, 4], [1, 5], [2, 6], [3, 7],
+];
+
+// Estrae l'isosuperficie `iso` da un campo scalare 3D con i dati `dims`
+fn marching_cubes_impl(field: &[f32], dims: [u32; 3], iso: f32) -> MeshData {
+    let (nx, ny, nz) = (dims[0] as usize, dims[1] as usize, dims[2] as usize);
+    if nx < 2 || ny < 2 || nz < 2 || field.len() < nx * ny * nz {
+        return MeshData { vertices: Vec::new(), indices: Vec::new() };
+    }
+    let idx = |x: usize, y: usize, z: usize| x + y * nx + z * nx * ny;
+
+    let mut vertices: Vec<f32> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    // Deduplica i vertici condivisi tramite la coppia di angoli globali dell'arco
+    let mut edge_cache: std::collections::HashMap<(u32, u32), u32> = std::collections::HashMap::new();
+
+    for z in 0..(nz - 1) {
+        for y in 0..(ny - 1) {
+            for x in 0..(nx - 1) {
+                // Valori degli 8 angoli e indice a 8 bit
+                let mut vals = [0.0f32; 8];
+                let mut cube_index = 0usize;
+                for (c, corner) in MC_CORNERS.iter().enumerate() {
+                    let (cx, cy, cz) = (x + corner[0] as usize, y + corner[1] as usize, z + corner[2] as usize);
+                    vals[c] = field[idx(cx, cy, cz)];
+                    if vals[c] < iso {
+                        cube_index |= 1 << c;
+                    }
+                }
+
+                let edges = MC_EDGE_TABLE[cube_index];
+                if edges == 0 {
+                    continue;
+                }
+
+                // Vertici interpolati lungo gli archi attivi
+                let mut edge_vertex = [0u32; 12];
+                for e in 0..12 {
+                    if edges & (1 << e) == 0 {
+                        continue;
+                    }
+                    let (ca, cb) = (MC_EDGE_CORNERS[e][0], MC_EDGE_CORNERS[e][1]);
+                    let ga = [x + MC_CORNERS[ca][0] as usize, y + MC_CORNERS[ca][1] as usize, z + MC_CORNERS[ca][2] as usize];
+                    let gb = [x + MC_CORNERS[cb][0] as usize, y + MC_CORNERS[cb][1] as usize, z + MC_CORNERS[cb][2] as usize];
+                    let ka = idx(ga[0], ga[1], ga[2]) as u32;
+                    let kb = idx(gb[0], gb[1], gb[2]) as u32;
+                    let key = if ka < kb { (ka, kb) } else { (kb, ka) };
+                    let vi = *edge_cache.entry(key).or_insert_with(|| {
+                        let va = vals[ca];
+                        let vb = vals[cb];
+                        let t = if (vb - va).abs() > 1e-12 { (iso - va) / (vb - va) } else { 0.5 };
+                        let p = [
+                            ga[0] as f32 + t * (gb[0] as f32 - ga[0] as f32),
+                            ga[1] as f32 + t * (gb[1] as f32 - ga[1] as f32),
+                            ga[2] as f32 + t * (gb[2] as f32 - ga[2] as f32),
+                        ];
+                        let vid = (vertices.len() / 3) as u32;
+                        vertices.extend_from_slice(&p);
+                        vid
+                    });
+                    edge_vertex[e] = vi;
+                }
+
+                // Emetti i triangoli della configurazione
+                let tri = &MC_TRI_TABLE[cube_index];
+                let mut i = 0;
+                while i < 16 && tri[i] != -1 {
+                    indices.push(edge_vertex[tri[i] as usize]);
+                    indices.push(edge_vertex[tri[i + 1] as usize]);
+                    indices.push(edge_vertex[tri[i + 2] as usize]);
+                    i += 3;
+                }
+            }
+        }
+    }
+
+    MeshData { vertices, indices }
+}
+
+// Serializza una mesh in STL binario
+fn build_binary_stl(vertices: &[f32], indices: &[u32]) -> Vec<u8> {
+    let tri_count = (indices.len() / 3) as u32;
+    let mut bytes: Vec<u8> = Vec::with_capacity(84 + tri_count as usize * 50);
+    // Header di 80 byte (vuoto) seguito dal conteggio dei triangoli
+    bytes.extend_from_slice(&[0u8; 80]);
+    bytes.extend_from_slice(&tri_count.to_le_bytes());
+
+    for t in 0..tri_count as usize {
+        let a = vertex_at(vertices, indices[t * 3] as usize);
+        let b = vertex_at(vertices, indices[t * 3 + 1] as usize);
+        let c = vertex_at(vertices, indices[t * 3 + 2] as usize);
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+
+        for &comp in &[normal.x, normal.y, normal.z] {
+            bytes.extend_from_slice(&comp.to_le_bytes());
+        }
+        for v in &[a, b, c] {
+            for &comp in &[v.x, v.y, v.z] {
+                bytes.extend_from_slice(&comp.to_le_bytes());
+            }
+        }
+        // Conteggio attributi a 16 bit
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+    }
+
+    bytes
+}
+
+// ---------------------------------------------------------------------------
+// Post-processing della mesh: saldatura, cache dei vertici e fetch locality
+// ---------------------------------------------------------------------------
+
+const FORSYTH_CACHE_SIZE: usize = 32;
+const FORSYTH_DECAY_POWER: f32 = 1.5;
+const FORSYTH_LAST_TRI_SCORE: f32 = 0.75;
+const FORSYTH_VALENCE_SCALE: f32 = 2.0;
+const FORSYTH_VALENCE_POWER: f32 = 0.5;
+
+// Salda i vertici near-duplicate quantizzando le posizioni su `epsilon`
+fn weld_vertices(vertices: &[f32], indices: &[u32], epsilon: f32) -> (Vec<f32>, Vec<u32>) {
+    let eps = if epsilon > 0.0 { epsilon } else { 1e-5 };
+    let inv = 1.0 / eps;
+    let mut map: std::collections::HashMap<(i64, i64, i64), u32> = std::collections::HashMap::new();
+    let mut out_verts: Vec<f32> = Vec::new();
+    let mut remap = vec![0u32; vertices.len() / 3];
+
+    for i in 0..(vertices.len() / 3) {
+        let key = (
+            (vertices[i * 3] * inv).round() as i64,
+            (vertices[i * 3 + 1] * inv).round() as i64,
+            (vertices[i * 3 + 2] * inv).round() as i64,
+        );
+        let id = *map.entry(key).or_insert_with(|| {
+            let id = (out_verts.len() / 3) as u32;
+            out_verts.push(vertices[i * 3]);
+            out_verts.push(vertices[i * 3 + 1]);
+            out_verts.push(vertices[i * 3 + 2]);
+            id
+        });
+        remap[i] = id;
+    }
+
+    let out_indices = indices.iter().map(|&i| remap[i as usize]).collect();
+    (out_verts, out_indices)
+}
+
+// Punteggio di un vertice dato dalla posizione in cache e dalla valenza
+fn forsyth_vertex_score(cache_pos: i32, valence: i32) -> f32 {
+    if valence <= 0 {
+        return -1.0;
+    }
+    let mut score = if cache_pos < 0 {
+        0.0
+    } else if cache_pos < 3 {
+        FORSYTH_LAST_TRI_SCORE
+    } else {
+        let denom = (FORSYTH_CACHE_SIZE - 3) as f32;
+        (1.0 - (cache_pos as f32 - 3.0) / denom).powf(FORSYTH_DECAY_POWER)
+    };
+    score += FORSYTH_VALENCE_SCALE * (valence as f32).powf(-FORSYTH_VALENCE_POWER);
+    score
+}
+
+// Riordina gli indici dei triangoli con l'algoritmo lineare di Tom Forsyth
+fn forsyth_optimize(indices: &[u32], vertex_count: usize) -> Vec<u32> {
+    let tri_count = indices.len() / 3;
+    if tri_count == 0 {
+        return Vec::new();
+    }
+
+    // Triangoli incidenti a ogni vertice e valenza attiva
+    let mut vert_tris: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    for t in 0..tri_count {
+        for k in 0..3 {
+            vert_tris[indices[t * 3 + k] as usize].push(t);
+        }
+    }
+    let mut valence: Vec<i32> = vert_tris.iter().map(|v| v.len() as i32).collect();
+
+    let mut cache_pos: Vec<i32> = vec![-1; vertex_count];
+    let mut vscore: Vec<f32> = (0..vertex_count).map(|v| forsyth_vertex_score(-1, valence[v])).collect();
+    let mut tscore: Vec<f32> = (0..tri_count)
+        .map(|t| vscore[indices[t * 3] as usize] + vscore[indices[t * 3 + 1] as usize] + vscore[indices[t * 3 + 2] as usize])
+        .collect();
+    let mut added = vec![false; tri_count];
+
+    // Miglior triangolo iniziale
+    let mut best_tri: i32 = -1;
+    let mut best_score = -1.0f32;
+    for (t, &score) in tscore.iter().enumerate() {
+        if score > best_score {
+            best_score = score;
+            best_tri = t as i32;
+        }
+    }
+
+    let mut cache: Vec<i32> = Vec::with_capacity(FORSYTH_CACHE_SIZE + 3);
+    let mut out: Vec<u32> = Vec::with_capacity(indices.len());
+
+    for _ in 0..tri_count {
+        if best_tri < 0 {
+            // Vicolo cieco: scansione lineare del miglior triangolo residuo
+            best_score = -1.0;
+            for t in 0..tri_count {
+                if !added[t] && tscore[t] > best_score {
+                    best_score = tscore[t];
+                    best_tri = t as i32;
+                }
+            }
+            if best_tri < 0 {
+                break;
+            }
+        }
+
+        let t = best_tri as usize;
+        added[t] = true;
+        let tri_verts = [indices[t * 3] as usize, indices[t * 3 + 1] as usize, indices[t * 3 + 2] as usize];
+        out.push(tri_verts[0] as u32);
+        out.push(tri_verts[1] as u32);
+        out.push(tri_verts[2] as u32);
+
+        // Riduci la valenza e rimuovi il triangolo dalle liste attive
+        for &v in &tri_verts {
+            valence[v] -= 1;
+            if let Some(pos) = vert_tris[v].iter().position(|&x| x == t) {
+                vert_tris[v].swap_remove(pos);
+            }
+        }
+
+        // Azzera le posizioni della cache precedente (reset locale)
+        for &c in &cache {
+            cache_pos[c as usize] = -1;
+        }
+
+        // Ricostruisci la cache portando in testa i vertici del triangolo
+        let mut new_cache: Vec<i32> = tri_verts.iter().map(|&v| v as i32).collect();
+        for &c in &cache {
+            if !tri_verts.contains(&(c as usize)) {
+                new_cache.push(c);
+            }
+        }
+        if new_cache.len() > FORSYTH_CACHE_SIZE + 3 {
+            new_cache.truncate(FORSYTH_CACHE_SIZE + 3);
+        }
+        cache = new_cache;
+
+        // Aggiorna le posizioni in cache e i punteggi dei vertici coinvolti
+        for (pos, &c) in cache.iter().enumerate() {
+            cache_pos[c as usize] = if pos < FORSYTH_CACHE_SIZE { pos as i32 } else { -1 };
+        }
+
+        // Ricalcola i punteggi dei triangoli incidenti ai vertici in cache
+        best_tri = -1;
+        best_score = -1.0;
+        for &c in &cache {
+            let cv = c as usize;
+            vscore[cv] = forsyth_vertex_score(cache_pos[cv], valence[cv]);
+        }
+        for &c in &cache {
+            let cv = c as usize;
+            for &tt in &vert_tris[cv] {
+                if added[tt] {
+                    continue;
+                }
+                tscore[tt] = vscore[indices[tt * 3] as usize]
+                    + vscore[indices[tt * 3 + 1] as usize]
+                    + vscore[indices[tt * 3 + 2] as usize];
+                if tscore[tt] > best_score {
+                    best_score = tscore[tt];
+                    best_tri = tt as i32;
+                }
+            }
+        }
+    }
+
+    out
+}
+
+// Riordina il buffer vertici per ordine di primo utilizzo (fetch locality)
+fn reorder_vertices(vertices: &[f32], indices: &[u32]) -> MeshData {
+    let vertex_count = vertices.len() / 3;
+    let mut remap = vec![u32::MAX; vertex_count];
+    let mut out_verts: Vec<f32> = Vec::with_capacity(vertices.len());
+    let mut out_indices: Vec<u32> = Vec::with_capacity(indices.len());
+
+    for &i in indices {
+        let v = i as usize;
+        if remap[v] == u32::MAX {
+            remap[v] = (out_verts.len() / 3) as u32;
+            out_verts.push(vertices[v * 3]);
+            out_verts.push(vertices[v * 3 + 1]);
+            out_verts.push(vertices[v * 3 + 2]);
+        }
+        out_indices.push(remap[v]);
+    }
+
+    MeshData { vertices: out_verts, indices: out_indices }
+}
+
+// Pipeline completa: saldatura, ottimizzazione della cache e riordino vertici
+fn optimize_mesh_impl(vertices: &[f32], indices: &[u32], epsilon: f32) -> MeshData {
+    let (welded_v, welded_i) = weld_vertices(vertices, indices, epsilon);
+    let cache_order = forsyth_optimize(&welded_i, welded_v.len() / 3);
+    reorder_vertices(&welded_v, &cache_order)
+}
+
+// ---------------------------------------------------------------------------
+// Tassellazione adattiva di curve e patch di Bézier (de Casteljau)
+// ---------------------------------------------------------------------------
+
+// Valuta una curva di Bézier (de Casteljau) al parametro t
+fn bezier_eval(cps: &[Vec3], t: f32) -> Vec3 {
+    let mut tmp = cps.to_vec();
+    let n = tmp.len();
+    for level in 1..n {
+        for i in 0..(n - level) {
+            tmp[i] = tmp[i] * (1.0 - t) + tmp[i + 1] * t;
+        }
+    }
+    tmp[0]
+}
+
+// Suddivide una curva di Bézier a t=0.5 nelle due metà
+fn bezier_subdivide(cps: &[Vec3]) -> (Vec<Vec3>, Vec<Vec3>) {
+    let n = cps.len();
+    let mut tmp = cps.to_vec();
+    let mut left = vec![tmp[0]];
+    let mut right = vec![tmp[n - 1]];
+    for level in 1..n {
+        for i in 0..(n - level) {
+            tmp[i] = (tmp[i] + tmp[i + 1]) * 0.5;
+        }
+        left.push(tmp[0]);
+        right.push(tmp[n - 1 - level]);
+    }
+    right.reverse();
+    (left, right)
+}
+
+// La curva è piatta se i punti di controllo interni distano dalla corda
+// meno della tolleranza
+fn bezier_flat(cps: &[Vec3], tolerance: f32) -> bool {
+    let n = cps.len();
+    if n <= 2 {
+        return true;
+    }
+    let a = cps[0];
+    let b = cps[n - 1];
+    let chord = b - a;
+    let len = chord.length();
+    for p in &cps[1..n - 1] {
+        let d = if len < 1e-12 {
+            (*p - a).length()
+        } else {
+            (*p - a).cross(chord).length() / len
+        };
+        if d > tolerance {
+            return false;
+        }
+    }
+    true
+}
+
+// Appende ricorsivamente i punti di leaf (estremo iniziale) di una curva
+fn bezier_flatten(cps: &[Vec3], tolerance: f32, depth: u32, out: &mut Vec<Vec3>) {
+    if depth == 0 || bezier_flat(cps, tolerance) {
+        out.push(cps[0]);
+        return;
+    }
+    let (left, right) = bezier_subdivide(cps);
+    bezier_flatten(&left, tolerance, depth - 1, out);
+    bezier_flatten(&right, tolerance, depth - 1, out);
+}
+
+// Tassella una (eventualmente composita) curva di Bézier del grado dato
+fn tessellate_bezier_curve(control: &[f32], degree: usize, tolerance: f32) -> Vec<f32> {
+    let pts: Vec<Vec3> = (0..control.len() / 3).map(|i| vertex_at(control, i)).collect();
+    let seg = degree + 1;
+    if pts.len() < seg || seg < 2 {
+        // Restituisci i punti così come sono quando non formano una curva valida
+        return control.to_vec();
+    }
+
+    let tol = if tolerance > 0.0 { tolerance } else { 1e-3 };
+    let mut out_pts: Vec<Vec3> = Vec::new();
+    let mut i = 0;
+    // Segmenti consecutivi che condividono l'estremo finale
+    while i + seg <= pts.len() {
+        bezier_flatten(&pts[i..i + seg], tol, 20, &mut out_pts);
+        i += degree;
+    }
+    // Aggiungi l'estremo finale dell'ultimo segmento tassellato
+    let last = pts[(i - degree) + seg - 1];
+    if out_pts.last() != Some(&last) {
+        out_pts.push(last);
+    }
+
+    let mut out = Vec::with_capacity(out_pts.len() * 3);
+    for p in out_pts {
+        out.push(p.x);
+        out.push(p.y);
+        out.push(p.z);
+    }
+    out
+}
+
+// Stima il numero di suddivisioni adattive per una riga di controllo
+fn adaptive_segments(cps: &[Vec3], tolerance: f32) -> usize {
+    let mut out: Vec<Vec3> = Vec::new();
+    bezier_flatten(cps, tolerance, 20, &mut out);
+    out.len().max(1)
+}
+
+// Tassella una patch di Bézier bicubica (4x4 punti di controllo)
+fn tessellate_bezier_patch_impl(control: &[f32], tolerance: f32) -> (Vec<f32>, Vec<u32>) {
+    if control.len() < 16 * 3 {
+        return (Vec::new(), Vec::new());
+    }
+    let tol = if tolerance > 0.0 { tolerance } else { 1e-3 };
+
+    // Griglia 4x4 dei punti di controllo (row-major in u, colonne in v)
+    let grid: Vec<Vec3> = (0..16).map(|i| vertex_at(control, i)).collect();
+    let cp = |u: usize, v: usize| grid[v * 4 + u];
+
+    // Suddivisioni adattive dalle righe e colonne di controllo nelle due direzioni
+    let mut su = 1;
+    let mut sv = 1;
+    for v in 0..4 {
+        let row = [cp(0, v), cp(1, v), cp(2, v), cp(3, v)];
+        su = su.max(adaptive_segments(&row, tol));
+    }
+    for u in 0..4 {
+        let col = [cp(u, 0), cp(u, 1), cp(u, 2), cp(u, 3)];
+        sv = sv.max(adaptive_segments(&col, tol));
+    }
+
+    // Campiona la superficie su una griglia (su+1)x(sv+1)
+    let mut vertices: Vec<f32> = Vec::new();
+    for j in 0..=sv {
+        let tv = j as f32 / sv as f32;
+        // Valuta le 4 curve-riga in u, poi in v
+        for i in 0..=su {
+            let tu = i as f32 / su as f32;
+            let mut row_pts = [Vec3::ZERO; 4];
+            for (v, rp) in row_pts.iter_mut().enumerate() {
+                let row = [cp(0, v), cp(1, v), cp(2, v), cp(3, v)];
+                *rp = bezier_eval(&row, tu);
+            }
+            let p = bezier_eval(&row_pts, tv);
+            vertices.push(p.x);
+            vertices.push(p.y);
+            vertices.push(p.z);
+        }
+    }
+
+    // Triangola la griglia
+    let stride = su + 1;
+    let mut indices: Vec<u32> = Vec::new();
+    for j in 0..sv {
+        for i in 0..su {
+            let a = (j * stride + i) as u32;
+            let b = a + 1;
+            let c = ((j + 1) * stride + i) as u32;
+            let d = c + 1;
+            indices.push(a);
+            indices.push(c);
+            indices.push(b);
+            indices.push(b);
+            indices.push(c);
+            indices.push(d);
+        }
+    }
+
+    (vertices, indices)
+}
+
+// ---------------------------------------------------------------------------
+// Frustum culling su piani (estrazione Gribb–Hartmann)
+// ---------------------------------------------------------------------------
+
+// Estrae i sei piani del frustum da una matrice view-projection e li normalizza.
+// Ogni piano (a,b,c,d) è orientato con la normale verso l'interno.
+fn extract_frustum_planes(vp: Mat4) -> [Vec4; 6] {
+    let r0 = vp.row(0);
+    let r1 = vp.row(1);
+    let r2 = vp.row(2);
+    let r3 = vp.row(3);
+
+    let mut planes = [
+        r3 + r0, // sinistra
+        r3 - r0, // destra
+        r3 + r1, // basso
+        r3 - r1, // alto
+        r3 + r2, // vicino
+        r3 - r2, // lontano
+    ];
+
+    for p in &mut planes {
+        let len = Vec3::new(p.x, p.y, p.z).length();
+        if len > 1e-12 {
+            *p /= len;
+        }
+    }
+
+    planes
+}
+
+// Distanza con segno di un punto da un piano (positiva = lato interno)
+fn plane_distance(plane: Vec4, p: Vec3) -> f32 {
+    plane.x * p.x + plane.y * p.y + plane.z * p.z + plane.w
+}
+
+// Percorso veloce AABB-vs-sfera: rigetta il box tramite la sua sfera avvolgente
+fn sphere_in_frustum(planes: &[Vec4; 6], center: Vec3, radius: f32) -> bool {
+    for plane in planes {
+        if plane_distance(*plane, center) < -radius {
+            return false;
+        }
+    }
+    true
+}
+
+// Test AABB-vs-frustum con il vertice positivo di ciascun piano
+fn aabb_in_frustum(planes: &[Vec4; 6], min: Vec3, max: Vec3) -> bool {
+    // Rigetto rapido tramite la sfera avvolgente del box
+    let center = (min + max) * 0.5;
+    let radius = (max - center).length();
+    if !sphere_in_frustum(planes, center, radius) {
+        return false;
+    }
+
+    for plane in planes {
+        // Vertice positivo: l'angolo più avanzato lungo la normale del piano
+        let positive = Vec3::new(
+            if plane.x >= 0.0 { max.x } else { min.x },
+            if plane.y >= 0.0 { max.y } else { min.y },
+            if plane.z >= 0.0 { max.z } else { min.z },
+        );
+        // Se anche il vertice positivo è dietro il piano, il box è escluso
+        if plane_distance(*plane, positive) < 0.0 {
+            return false;
+        }
+    }
+    true
+}
+
+// Macro per il logging console
+#[macro_export]
+macro_rules! console_log {
+    ($($t:tt)*) => {
+        web_sys::console::log_1(&format!($($t)*).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cubo unitario saldato (8 vertici, 12 triangoli) traslato di `offset` e scalato di `size`
+    fn cube_mesh(offset: Vec3, size: f32) -> MeshData {
+        let corners = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(0.0, 1.0, 1.0),
+        ];
+        let mut vertices = Vec::with_capacity(24);
+        for c in &corners {
+            let p = offset + *c * size;
+            vertices.push(p.x);
+            vertices.push(p.y);
+            vertices.push(p.z);
+        }
+        let indices: Vec<u32> = vec![
+            0, 4, 7, 0, 7, 3, // -X
+            1, 2, 6, 1, 6, 5, // +X
+            0, 1, 5, 0, 5, 4, // -Y
+            3, 7, 6, 3, 6, 2, // +Y
+            0, 3, 2, 0, 2, 1, // -Z
+            4, 5, 6, 4, 6, 7, // +Z
+        ];
+        MeshData { vertices, indices }
+    }
+
+    #[test]
+    fn qem_simplify_reduces_triangles_without_degenerate_geometry() {
+        let cube = cube_mesh(Vec3::ZERO, 1.0);
+        let target = cube.indices.len() / 3 / 2;
+        let simplified = qem_simplify(&cube.vertices, &cube.indices, target);
+
+        assert!(simplified.indices.len() / 3 < cube.indices.len() / 3);
+        assert!(!simplified.indices.is_empty());
+        for &v in &simplified.vertices {
+            assert!(v.is_finite());
+        }
+        for tri in simplified.indices.chunks(3) {
+            assert_ne!(tri[0], tri[1]);
+            assert_ne!(tri[1], tri[2]);
+            assert_ne!(tri[0], tri[2]);
+        }
+    }
+
+    // Una mesh è chiusa (watertight) se ogni arco non orientato è condiviso da esattamente due triangoli
+    fn is_watertight(mesh: &MeshData) -> bool {
+        if mesh.indices.is_empty() {
+            return false;
+        }
+        let mut edge_count: std::collections::HashMap<(u32, u32), u32> = std::collections::HashMap::new();
+        for tri in mesh.indices.chunks(3) {
+            for &(a, b) in &[(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+                let key = if a < b { (a, b) } else { (b, a) };
+                *edge_count.entry(key).or_insert(0) += 1;
+            }
+        }
+        edge_count.values().all(|&c| c == 2)
+    }
+
+    #[test]
+    fn csg_union_of_overlapping_cubes_is_watertight() {
+        let a = cube_mesh(Vec3::ZERO, 1.0);
+        let b = cube_mesh(Vec3::splat(0.5), 1.0);
+        let result = csg_operation(&a, &b, CsgOp::Union);
+        assert!(is_watertight(&result));
+    }
+
+    #[test]
+    fn csg_subtract_of_overlapping_cubes_is_watertight() {
+        let a = cube_mesh(Vec3::ZERO, 1.0);
+        let b = cube_mesh(Vec3::splat(0.5), 1.0);
+        let result = csg_operation(&a, &b, CsgOp::Subtract);
+        assert!(is_watertight(&result));
+    }
+
+    #[test]
+    fn csg_intersect_of_overlapping_cubes_is_watertight() {
+        let a = cube_mesh(Vec3::ZERO, 1.0);
+        let b = cube_mesh(Vec3::splat(0.5), 1.0);
+        let result = csg_operation(&a, &b, CsgOp::Intersect);
+        assert!(is_watertight(&result));
+    }
+
+    #[test]
+    fn quickhull_of_cube_excludes_interior_point() {
+        let cube = cube_mesh(Vec3::ZERO, 1.0);
+        let mut vertices = cube.vertices.clone();
+        vertices.push(0.5);
+        vertices.push(0.5);
+        vertices.push(0.5);
+
+        let hull = quickhull(&vertices, 0);
+
+        assert_eq!(hull.vertices.len() / 3, 8);
+        assert_eq!(hull.indices.len() / 3, 12);
+    }
+
+    // Griglia piatta di `n * n` vertici su XY con 2*(n-1)^2 triangoli, abbastanza
+    // densa da superare MESHLET_MAX_TRIANGLES e produrre più livelli di LOD
+    fn grid_mesh(n: usize) -> MeshData {
+        let mut vertices = Vec::with_capacity(n * n * 3);
+        for y in 0..n {
+            for x in 0..n {
+                vertices.push(x as f32);
+                vertices.push(y as f32);
+                vertices.push(0.0);
+            }
+        }
+        let mut indices = Vec::new();
+        for y in 0..(n - 1) {
+            for x in 0..(n - 1) {
+                let i0 = (y * n + x) as u32;
+                let i1 = i0 + 1;
+                let i2 = i0 + n as u32;
+                let i3 = i2 + 1;
+                indices.extend_from_slice(&[i0, i2, i1, i1, i2, i3]);
+            }
+        }
+        MeshData { vertices, indices }
+    }
+
+    #[test]
+    fn build_meshlet_dag_has_monotonically_increasing_error_across_levels() {
+        let grid = grid_mesh(20);
+        let dag = build_meshlet_dag(&grid.vertices, &grid.indices);
+
+        assert!(dag.levels.len() >= 2, "dense grid should collapse into multiple LOD levels");
+        for pair in dag.levels.windows(2) {
+            assert!(pair[1].error >= pair[0].error);
+        }
+        for meshlet in &dag.meshlets {
+            assert!(meshlet.error <= meshlet.parent_error);
+        }
+    }
+
+    #[test]
+    fn validate_atlas_limits_rejects_unbounded_resolution_and_padding() {
+        assert!(validate_atlas_limits(0, 2).is_err());
+        assert!(validate_atlas_limits(100_000, 2).is_err());
+        assert!(validate_atlas_limits(1024, MAX_ATLAS_PADDING + 1).is_err());
+        assert!(validate_atlas_limits(1024, 2).is_ok());
+    }
+
+    // Campo scalare di una sfera centrata nella griglia: positivo dentro, negativo fuori
+    fn sphere_field(dim: usize, radius: f32) -> Vec<f32> {
+        let center = (dim as f32 - 1.0) / 2.0;
+        let mut field = Vec::with_capacity(dim * dim * dim);
+        for z in 0..dim {
+            for y in 0..dim {
+                for x in 0..dim {
+                    let d = ((x as f32 - center).powi(2)
+                        + (y as f32 - center).powi(2)
+                        + (z as f32 - center).powi(2))
+                    .sqrt();
+                    field.push(radius - d);
+                }
+            }
+        }
+        field
+    }
+
+    #[test]
+    fn marching_cubes_and_stl_export_round_trip() {
+        let dim = 8usize;
+        let field = sphere_field(dim, 3.0);
+        let mesh = marching_cubes_impl(&field, [dim as u32, dim as u32, dim as u32], 0.0);
+
+        assert!(!mesh.indices.is_empty());
+        assert_eq!(mesh.indices.len() % 3, 0);
+        let tri_count = mesh.indices.len() / 3;
+
+        let stl = build_binary_stl(&mesh.vertices, &mesh.indices);
+        assert_eq!(stl.len(), 84 + 50 * tri_count);
+        assert!(stl[0..80].iter().all(|&b| b == 0));
+        let header_tri_count = u32::from_le_bytes([stl[80], stl[81], stl[82], stl[83]]);
+        assert_eq!(header_tri_count as usize, tri_count);
+    }
+
+    // Cubo con vertici duplicati per faccia (24 vertici), non ancora saldato
+    fn unwelded_cube_mesh() -> MeshData {
+        let corners = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(0.0, 1.0, 1.0),
+        ];
+        let faces: [[usize; 4]; 6] = [
+            [0, 4, 7, 3], // -X
+            [1, 2, 6, 5], // +X
+            [0, 1, 5, 4], // -Y
+            [3, 7, 6, 2], // +Y
+            [0, 3, 2, 1], // -Z
+            [4, 5, 6, 7], // +Z
+        ];
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for face in &faces {
+            let base = (vertices.len() / 3) as u32;
+            for &c in face {
+                let p = corners[c];
+                vertices.push(p.x);
+                vertices.push(p.y);
+                vertices.push(p.z);
+            }
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+        MeshData { vertices, indices }
+    }
+
+    #[test]
+    fn optimize_mesh_impl_is_idempotent_on_vertex_and_triangle_counts() {
+        let raw = unwelded_cube_mesh();
+        let first = optimize_mesh_impl(&raw.vertices, &raw.indices, 1e-5);
+        let second = optimize_mesh_impl(&first.vertices, &first.indices, 1e-5);
+
+        assert_eq!(first.vertices.len(), second.vertices.len());
+        assert_eq!(first.indices.len(), second.indices.len());
+        assert_eq!(first.vertices.len() / 3, 8);
+    }
+
+    #[test]
+    fn tessellate_bezier_curve_respects_flatness_tolerance() {
+        // Curva quadratica con un bulge pronunciato: deviazione dalla corda molto
+        // maggiore delle tolleranze usate qui, quindi deve essere suddivisa
+        let bulge = vec![0.0, 0.0, 0.0, 5.0, 10.0, 0.0, 10.0, 0.0, 0.0];
+        let loose = tessellate_bezier_curve(&bulge, 2, 1.0);
+        let tight = tessellate_bezier_curve(&bulge, 2, 0.01);
+        assert!(tight.len() / 3 > loose.len() / 3);
+
+        // Una retta è già piatta per qualunque tolleranza: nessuna suddivisione
+        let line = vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 2.0, 0.0, 0.0];
+        let out = tessellate_bezier_curve(&line, 2, 0.01);
+        assert_eq!(out.len() / 3, 2);
+    }
+
+    #[test]
+    fn aabb_in_frustum_accepts_inside_and_rejects_outside_box() {
+        let vp = Mat4::orthographic_rh(-1.0, 1.0, -1.0, 1.0, 0.1, 10.0);
+        let planes = extract_frustum_planes(vp);
+
+        // Interamente dentro il volume di vista
+        assert!(aabb_in_frustum(&planes, Vec3::new(-0.5, -0.5, -2.0), Vec3::new(0.5, 0.5, -1.0)));
+
+        // Ben oltre il piano destro/alto del frustum
+        assert!(!aabb_in_frustum(&planes, Vec3::new(5.0, 5.0, -2.0), Vec3::new(6.0, 6.0, -1.0)));
     }
 }
\ No newline at end of file